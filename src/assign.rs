@@ -21,10 +21,7 @@ impl Assignment {
     }
     #[inline]
     pub(crate) fn value(&self, lit: &Lit) -> Option<bool> {
-        match self.assignment[lit.index()] {
-            Some(assign) => Some(assign == lit.is_positive()),
-            None => None,
-        }
+        self.assignment[lit.index()].map(|assign| assign == lit.is_positive())
     }
     #[inline]
     pub(crate) fn is_true(&self, lit: &Lit) -> bool {
@@ -34,10 +31,6 @@ impl Assignment {
     pub(crate) fn is_false(&self, lit: &Lit) -> bool {
         self.value(lit) == Some(false)
     }
-    #[inline]
-    pub(crate) fn is_none(&self, lit: &Lit) -> bool {
-        self.value(lit) == None
-    }
 }
 impl Deref for Assignment {
     type Target = Vec<Option<bool>>;
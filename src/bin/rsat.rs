@@ -0,0 +1,126 @@
+use std::{
+    env,
+    io::{self, BufWriter, Write},
+    process::ExitCode,
+};
+
+use anyhow::{bail, Context, Result};
+use rsat::{
+    cnf::CnfFormula,
+    lit::Lit,
+    solver::{SolveState, Solver},
+};
+
+const USAGE: &str = "usage: rsat [INPUT]
+
+Solves the DIMACS CNF formula in INPUT, which may be gzip, xz or bzip2
+compressed. Reads from stdin if INPUT is missing or `-`.
+
+Exits with 10 if the formula is satisfiable, 20 if it is unsatisfiable and 0
+if the result is unknown.";
+
+fn main() -> Result<ExitCode> {
+    #[cfg(feature = "log")]
+    logger::init();
+    let mut input = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(ExitCode::SUCCESS);
+            }
+            _ if input.is_some() => bail!("unexpected argument `{}`\n\n{}", arg, USAGE),
+            _ => input = Some(arg),
+        }
+    }
+    let formula = match input.as_deref() {
+        None | Some("-") => CnfFormula::from_dimacs_reader(io::stdin().lock())
+            .context("failed to parse formula from stdin")?,
+        Some(path) => CnfFormula::from_dimacs_file(path)
+            .with_context(|| format!("failed to parse formula from `{}`", path))?,
+    };
+
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    writeln!(
+        stdout,
+        "c parsed {} variables and {} clauses",
+        formula.var_count(),
+        formula.iter().count()
+    )?;
+    stdout.flush()?;
+    let solver = Solver::default().add_formula(&formula).solve();
+    write!(stdout, "{}", solver.stats())?;
+    let code = match solver.state() {
+        SolveState::Sat => {
+            writeln!(stdout, "s SATISFIABLE")?;
+            write_model(&mut stdout, &solver.model().unwrap_or_default())?;
+            10
+        }
+        SolveState::Unsat => {
+            writeln!(stdout, "s UNSATISFIABLE")?;
+            20
+        }
+        SolveState::Unknown => {
+            writeln!(stdout, "s UNKNOWN")?;
+            0
+        }
+    };
+    stdout.flush()?;
+    Ok(ExitCode::from(code))
+}
+
+/// Writes the model as `v` lines, wrapped to keep lines reasonably short.
+fn write_model<W: Write>(writer: &mut W, model: &[Lit]) -> io::Result<()> {
+    const LINE_WIDTH: usize = 78;
+    let mut line = String::from("v");
+    for lit in model {
+        let value = if lit.is_negative() {
+            format!(" -{}", lit.to_dimacs())
+        } else {
+            format!(" {}", lit.to_dimacs())
+        };
+        if line.len() + value.len() > LINE_WIDTH {
+            writeln!(writer, "{}", line)?;
+            line.truncate(1);
+        }
+        line.push_str(&value);
+    }
+    writeln!(writer, "{} 0", line)
+}
+
+/// Logs to stderr as DIMACS comments, at the level given by the `RSAT_LOG`
+/// environment variable (`error`, `warn`, `info`, `debug` or `trace`).
+#[cfg(feature = "log")]
+mod logger {
+    use std::{env, io::Write};
+
+    use log::{LevelFilter, Log, Metadata, Record};
+
+    struct StderrLogger;
+    impl Log for StderrLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= log::max_level()
+        }
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                let _ = writeln!(
+                    std::io::stderr().lock(),
+                    "c [{}] {}",
+                    record.level(),
+                    record.args()
+                );
+            }
+        }
+        fn flush(&self) {}
+    }
+
+    pub(crate) fn init() {
+        let level = env::var("RSAT_LOG")
+            .ok()
+            .and_then(|level| level.parse().ok())
+            .unwrap_or(LevelFilter::Off);
+        if log::set_logger(&StderrLogger).is_ok() {
+            log::set_max_level(level);
+        }
+    }
+}
@@ -1,16 +1,31 @@
-use std::mem::replace;
+use std::mem::take;
 
 use crate::{
+    config::MinimizeMode,
+    db::ClauseDb,
     graph::{PropGraph, PropReason},
     lit::Lit,
     prop::PropQueue,
     solver::{ClauseRef, Solver},
 };
-use anyhow::Result;
 #[derive(Debug)]
 pub(crate) enum Conflict {
-    Binary([Lit; 2]),
-    Long(Vec<Lit>),
+    /// The falsified binary clause and its index in `ClauseDb::binary_clauses`.
+    Binary([Lit; 2], usize),
+    /// Index of the falsified clause in `ClauseDb::long_clauses`.
+    Long(usize),
+}
+impl Conflict {
+    /// The id and literals of the falsified clause.
+    pub(crate) fn clause<'a>(&'a self, clause_db: &'a ClauseDb) -> (u64, &'a [Lit]) {
+        match self {
+            Conflict::Binary(lits, index) => (clause_db.binary_ids[*index], lits),
+            Conflict::Long(index) => {
+                let clause = &clause_db.long_clauses[*index];
+                (clause.id, clause)
+            }
+        }
+    }
 }
 #[derive(Debug, Default)]
 pub(crate) struct AnalyzeConflict {
@@ -18,25 +33,75 @@ pub(crate) struct AnalyzeConflict {
     to_search_node: usize,
     learnt_clause: Vec<Lit>,
     to_clean: Vec<Lit>,
+    lbd: usize,
+    minimize: MinimizeMode,
+    /// Work stack of `is_redundant`.
+    stack: Vec<Lit>,
+    /// Long clauses resolved on in the current analysis.
+    used_clauses: Vec<usize>,
+    /// Marks decision levels already counted by `compute_lbd`, indexed by level.
+    level_stamp: Vec<u64>,
+    stamp: u64,
+    /// Marks the literals `collect_hints` still has to justify.
+    implied: Vec<bool>,
+    /// LRAT hints deriving the last learnt clause.
+    hints: Vec<u64>,
 }
 
 impl AnalyzeConflict {
+    pub(crate) fn new(minimize: MinimizeMode) -> Self {
+        AnalyzeConflict {
+            minimize,
+            ..Default::default()
+        }
+    }
     pub(crate) fn resize(&mut self, var_count: usize) {
         self.seen.resize(var_count, false);
+        self.implied.resize(var_count, false);
+    }
+    /// The LBD (glue) of the last learnt clause.
+    pub(crate) fn lbd(&self) -> usize {
+        self.lbd
+    }
+    /// Counts the distinct decision levels of the literals in `clause`.
+    pub(crate) fn compute_lbd(&mut self, prop_graph: &PropGraph, clause: &[Lit]) -> usize {
+        self.stamp += 1;
+        let mut lbd = 0;
+        for lit in clause {
+            let level = prop_graph.get_node(lit).level;
+            if level >= self.level_stamp.len() {
+                self.level_stamp.resize(level + 1, 0);
+            }
+            if self.level_stamp[level] != self.stamp {
+                self.level_stamp[level] = self.stamp;
+                lbd += 1;
+            }
+        }
+        lbd
+    }
+    /// Takes the LRAT hints of the last learnt clause, empty unless an LRAT
+    /// proof is written.
+    pub(crate) fn take_hints(&mut self) -> Vec<u64> {
+        take(&mut self.hints)
     }
     pub(crate) fn get_learnt_clause(&mut self) -> Option<Vec<Lit>> {
-        if self.learnt_clause.len() == 0 {
+        if self.learnt_clause.is_empty() {
             None
         } else {
-            Some(replace(&mut self.learnt_clause, vec![]))
+            Some(take(&mut self.learnt_clause))
         }
     }
 }
 impl Solver {
-    pub(crate) fn analyze_conflict(&mut self, conflict: Conflict) -> Result<usize> {
-        let clause = match conflict {
-            Conflict::Binary(ref v) => v,
-            Conflict::Long(ref v) => v.as_slice(),
+    pub(crate) fn analyze_conflict(&mut self, conflict: Conflict) -> usize {
+        let used_clauses = &mut self.analyze_conflict.used_clauses;
+        used_clauses.clear();
+        let clause: &[Lit] = match conflict {
+            Conflict::Binary(ref v, _) => v,
+            Conflict::Long(index) => {
+                used_clauses.push(index);
+                &self.clause_db.long_clauses[index]
+            }
         };
         let seen = &mut self.analyze_conflict.seen;
         let to_search_node = &mut self.analyze_conflict.to_search_node;
@@ -57,20 +122,18 @@ impl Solver {
         for lit in self.prop_queue.iter().rev() {
             if seen[lit.index()] {
                 seen[lit.index()] = false;
+                self.vsids.bump(lit.index());
                 if *to_search_node == 1 {
                     learnt_clause.push(!*lit);
                     let last = learnt_clause.len() - 1;
                     learnt_clause.swap(0, last);
                     break;
                 } else {
-                    let clause: &[Lit] = match self.prop_graph.get_node(lit).reason {
-                        PropReason::Unit => &[],
-                        PropReason::Binary(ref block) => block,
-                        PropReason::Long(clause_ref) => match clause_ref {
-                            ClauseRef::Binary(_) => &[],
-                            ClauseRef::Long(cref) => &self.clause_db.long_clauses[cref][1..],
-                        },
-                    };
+                    let reason = &self.prop_graph.get_node(lit).reason;
+                    if let PropReason::Long(ClauseRef::Long(index)) = reason {
+                        used_clauses.push(*index);
+                    }
+                    let clause = reason.lits(&self.clause_db);
                     AnalyzeConflict::analyze_clause(
                         &self.prop_graph,
                         &self.prop_queue,
@@ -81,13 +144,23 @@ impl Solver {
                         learnt_clause,
                     );
                 }
-                if *to_search_node == 0 {
-                    print!("hha");
-                }
+                debug_assert!(*to_search_node > 0, "no literal left on the conflict level");
                 *to_search_node -= 1;
             }
         }
-        for ele in to_clean {
+        for ele in to_clean.iter() {
+            self.vsids.bump(ele.index());
+        }
+        self.vsids.decay();
+        self.stats.learnt_literals_unminimized += learnt_clause.len() as u64;
+        self.minimize_learnt_clause();
+        let AnalyzeConflict {
+            seen,
+            to_clean,
+            learnt_clause,
+            ..
+        } = &mut self.analyze_conflict;
+        for ele in to_clean.iter() {
             seen[ele.index()] = false;
         }
         let mut backtrack = 0;
@@ -102,21 +175,209 @@ impl Solver {
                 }
             }
         }
-        Ok(backtrack)
+        let used_clauses = take(&mut self.analyze_conflict.used_clauses);
+        for &index in &used_clauses {
+            if self.clause_db.long_clauses[index].learnt {
+                let lbd = self
+                    .analyze_conflict
+                    .compute_lbd(&self.prop_graph, &self.clause_db.long_clauses[index]);
+                self.clause_db.mark_used(index, lbd);
+            }
+        }
+        self.analyze_conflict.used_clauses = used_clauses;
+        let learnt_clause = take(&mut self.analyze_conflict.learnt_clause);
+        self.analyze_conflict.lbd = self
+            .analyze_conflict
+            .compute_lbd(&self.prop_graph, &learnt_clause);
+        self.analyze_conflict.learnt_clause = learnt_clause;
+        self.collect_hints(&conflict);
+        backtrack
+    }
+    /// Collects the LRAT hints deriving the learnt clause: the units of the
+    /// level 0 literals involved, then the reasons of the literals resolved
+    /// away or removed by minimization in trail order, then the conflict.
+    fn collect_hints(&mut self, conflict: &Conflict) {
+        if !self.proof.as_ref().is_some_and(|proof| proof.is_lrat()) {
+            return;
+        }
+        let analyze = &mut self.analyze_conflict;
+        // the negation of the learnt clause is where the derivation starts
+        for lit in analyze.learnt_clause.iter() {
+            analyze.seen[lit.index()] = true;
+        }
+        let (conflict_id, conflict_lits) = conflict.clause(&self.clause_db);
+        let mut pending = mark_implied(&analyze.seen, &mut analyze.implied, conflict_lits);
+        let mut root_lits = Vec::new();
+        let mut reasons = Vec::new();
+        for lit in self.prop_queue.iter().rev() {
+            if pending == 0 {
+                break;
+            }
+            if !analyze.implied[lit.index()] {
+                continue;
+            }
+            analyze.implied[lit.index()] = false;
+            pending -= 1;
+            let node = self.prop_graph.get_node(lit);
+            if node.level == 0 {
+                root_lits.push(*lit);
+                continue;
+            }
+            let reason_id = node.reason.id(&self.clause_db);
+            reasons.push(reason_id.expect("only decisions have no reason"));
+            let reason_lits = node.reason.lits(&self.clause_db);
+            pending += mark_implied(&analyze.seen, &mut analyze.implied, reason_lits);
+        }
+        for lit in analyze.learnt_clause.iter() {
+            analyze.seen[lit.index()] = false;
+        }
+        let mut hints: Vec<u64> = root_lits
+            .into_iter()
+            .map(|lit| self.proof_unit_id(lit))
+            .collect();
+        hints.extend(reasons.iter().rev());
+        hints.push(conflict_id);
+        self.analyze_conflict.hints = hints;
+    }
+    /// Removes the literals implied by the other literals of the learnt
+    /// clause. All literals of the clause must still be marked `seen`.
+    fn minimize_learnt_clause(&mut self) {
+        let mode = self.analyze_conflict.minimize;
+        let mut learnt_clause = take(&mut self.analyze_conflict.learnt_clause);
+        if mode != MinimizeMode::None && learnt_clause.len() > 1 {
+            let abstract_levels = learnt_clause[1..].iter().fold(0, |levels, lit| {
+                levels | abstract_level(self.prop_graph.get_node(lit).level)
+            });
+            let mut kept = 1;
+            for i in 1..learnt_clause.len() {
+                let lit = learnt_clause[i];
+                let redundant = match mode {
+                    MinimizeMode::Basic => self.is_implied(lit),
+                    _ => self.is_redundant(lit, abstract_levels),
+                };
+                if !redundant {
+                    learnt_clause[kept] = lit;
+                    kept += 1;
+                }
+            }
+            learnt_clause.truncate(kept);
+        }
+        self.analyze_conflict.learnt_clause = learnt_clause;
+    }
+    /// Whether every literal of `lit`'s reason is in the learnt clause or
+    /// assigned at level 0.
+    fn is_implied(&self, lit: Lit) -> bool {
+        let reason = &self.prop_graph.get_node(&lit).reason;
+        if let PropReason::Unit = reason {
+            return false;
+        }
+        reason.lits(&self.clause_db).iter().all(|reason_lit| {
+            self.analyze_conflict.seen[reason_lit.index()]
+                || self.prop_graph.get_node(reason_lit).level == 0
+        })
+    }
+    /// Whether `lit` is implied by the learnt clause, following the reasons
+    /// recursively as in MiniSat. Literals proven redundant along the way stay
+    /// marked `seen` and are added to `to_clean`.
+    fn is_redundant(&mut self, lit: Lit, abstract_levels: u32) -> bool {
+        if let PropReason::Unit = self.prop_graph.get_node(&lit).reason {
+            return false;
+        }
+        let analyze = &mut self.analyze_conflict;
+        let top = analyze.to_clean.len();
+        analyze.stack.clear();
+        analyze.stack.push(lit);
+        while let Some(implied) = analyze.stack.pop() {
+            let reason = &self.prop_graph.get_node(&implied).reason;
+            for reason_lit in reason.lits(&self.clause_db) {
+                let node = self.prop_graph.get_node(reason_lit);
+                if analyze.seen[reason_lit.index()] || node.level == 0 {
+                    continue;
+                }
+                // a decision, or a level not in the clause at all, can't be
+                // implied by the clause
+                if !matches!(node.reason, PropReason::Unit)
+                    && abstract_levels & abstract_level(node.level) != 0
+                {
+                    analyze.seen[reason_lit.index()] = true;
+                    analyze.stack.push(*reason_lit);
+                    analyze.to_clean.push(*reason_lit);
+                } else {
+                    for cleaned in analyze.to_clean[top..].iter() {
+                        analyze.seen[cleaned.index()] = false;
+                    }
+                    analyze.to_clean.truncate(top);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+    /// Collects the assumptions that imply `!failed`, `failed` being an
+    /// assumption found false, by walking the implication graph back to the
+    /// assumption decisions. The result, including `failed` itself, is stored
+    /// in `failed_assumptions`.
+    pub(crate) fn analyze_final(&mut self, failed: Lit) {
+        self.failed_assumptions.clear();
+        self.failed_assumptions.push(failed);
+        if self.prop_graph.get_node(&failed).level == 0 {
+            return;
+        }
+        let seen = &mut self.analyze_conflict.seen;
+        seen[failed.index()] = true;
+        for lit in self.prop_queue.iter().rev() {
+            if !seen[lit.index()] {
+                continue;
+            }
+            seen[lit.index()] = false;
+            let node = self.prop_graph.get_node(lit);
+            if node.level == 0 {
+                continue;
+            }
+            match node.reason {
+                // every decision made so far is an assumption
+                PropReason::Unit => self.failed_assumptions.push(*lit),
+                ref reason => {
+                    for reason_lit in reason.lits(&self.clause_db) {
+                        if self.prop_graph.get_node(reason_lit).level > 0 {
+                            seen[reason_lit.index()] = true;
+                        }
+                    }
+                }
+            }
+        }
     }
 }
+/// Marks the literals of `lits` that are neither in the learnt clause nor
+/// marked yet, returning how many were marked.
+fn mark_implied(seen: &[bool], implied: &mut [bool], lits: &[Lit]) -> usize {
+    let mut marked = 0;
+    for lit in lits {
+        if !seen[lit.index()] && !implied[lit.index()] {
+            implied[lit.index()] = true;
+            marked += 1;
+        }
+    }
+    marked
+}
+/// A 32 bit signature of a decision level, used to quickly rule out literals
+/// whose level doesn't occur in the learnt clause.
+#[inline]
+fn abstract_level(level: usize) -> u32 {
+    1 << (level & 31)
+}
 impl AnalyzeConflict {
     fn analyze_clause(
         prop_graph: &PropGraph,
         prop_queue: &PropQueue,
         clause: &[Lit],
-        seen: &mut Vec<bool>,
+        seen: &mut [bool],
         to_search_node: &mut usize,
         to_clean: &mut Vec<Lit>,
         learnt_clause: &mut Vec<Lit>,
     ) {
         for lit in clause {
-            let node = prop_graph.get_node(&lit);
+            let node = prop_graph.get_node(lit);
             if !seen[lit.index()] && node.level > 0 {
                 seen[lit.index()] = true;
                 if node.level == prop_queue.current_level() {
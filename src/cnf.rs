@@ -1,47 +1,329 @@
-use std::{collections::HashSet, ops::Range};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufRead, Read, Write},
+    iter::once,
+    ops::Range,
+    path::Path,
+};
 
-use super::lit::Lit;
-use anyhow::{bail, Result};
+use super::{
+    compress::decompress,
+    error::{DimacsError, RsatError, UnsatisfiedClause},
+    lit::{Lit, Var},
+};
 #[derive(Clone, Default, Debug)]
 pub struct CnfFormula {
-    max_lit_index: usize,
+    var_count: usize,
     literals: Vec<Lit>,
     clause_ranges: Vec<Range<usize>>,
+    /// Input position of each clause, see [`Self::iter_with_ids`].
+    clause_ids: Vec<u64>,
+    tautologies: usize,
+    duplicate_literals: usize,
+}
+/// What [`CnfFormula::try_add_clause`] did with a clause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddClauseOutcome {
+    Added,
+    /// The clause contains a literal and its negation, so it was dropped.
+    Tautology,
+    /// The clause was added with `duplicates` repeated literals removed.
+    Simplified {
+        duplicates: usize,
+    },
 }
 impl CnfFormula {
     pub fn new() -> Self {
         CnfFormula::default()
     }
+    /// Parses a DIMACS CNF formula, see [`parse_dimacs`].
+    ///
+    /// gzip, xz and bzip2 compressed input is detected by its magic bytes and
+    /// decompressed while parsing.
+    pub fn from_dimacs_reader<R: Read>(reader: R) -> Result<Self, DimacsError> {
+        parse_dimacs(decompress(reader)?)
+    }
+    /// Parses a possibly compressed DIMACS CNF file, see [`Self::from_dimacs_reader`].
+    pub fn from_dimacs_file<P: AsRef<Path>>(path: P) -> Result<Self, DimacsError> {
+        Self::from_dimacs_reader(File::open(path)?)
+    }
+    /// Writes the formula in DIMACS CNF format.
+    pub fn write_dimacs<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write_dimacs_header(&mut writer, self.var_count(), self.clause_ranges.len())?;
+        for clause in self.iter() {
+            write_dimacs_clause(&mut writer, clause)?;
+        }
+        writer.flush()
+    }
+    /// Number of variables: the highest variable used in any clause, or
+    /// more if further ones were allocated or declared.
+    pub fn var_count(&self) -> usize {
+        self.var_count
+    }
+    /// Allocates a variable that isn't used by any clause yet.
+    ///
+    /// # Panics
+    ///
+    /// If the formula already has [`Lit::MAX_VAR`] variables.
+    pub fn new_var(&mut self) -> Var {
+        let var = Var::from_index(self.var_count);
+        self.var_count += 1;
+        var
+    }
+    /// The positive literal of a fresh variable, see [`Self::new_var`].
+    pub fn new_lit(&mut self) -> Lit {
+        self.new_var().lit(true)
+    }
+    /// Makes sure the formula has at least `var_count` variables, even if
+    /// some of them appear in no clause, like the ones a DIMACS header
+    /// declares.
+    ///
+    /// # Panics
+    ///
+    /// If `var_count` is above [`Lit::MAX_VAR`], see
+    /// [`Self::try_declare_vars`].
+    pub fn declare_vars(&mut self, var_count: usize) {
+        self.try_declare_vars(var_count)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Like [`Self::declare_vars`], but fails with
+    /// [`RsatError::VarOutOfRange`] if `var_count` is above [`Lit::MAX_VAR`].
+    pub fn try_declare_vars(&mut self, var_count: usize) -> Result<(), RsatError> {
+        if var_count > Lit::MAX_VAR {
+            return Err(RsatError::VarOutOfRange {
+                var: var_count,
+                max_var: Lit::MAX_VAR,
+            });
+        }
+        self.var_count = self.var_count.max(var_count);
+        Ok(())
+    }
     pub fn iter(&self) -> impl Iterator<Item = &[Lit]> {
         let lits = &self.literals;
         self.clause_ranges.iter().map(|range| &lits[range.clone()])
     }
+    /// Iterates over the clauses with their ids, which number every clause
+    /// passed to [`Self::add_clause`] from 1, dropped tautologies included.
+    /// That is the clause order of the DIMACS input, which LRAT proofs refer
+    /// to.
+    pub fn iter_with_ids(&self) -> impl Iterator<Item = (u64, &[Lit])> {
+        self.clause_ids.iter().copied().zip(self.iter())
+    }
+    /// Number of clauses passed to [`Self::add_clause`], tautologies
+    /// included, so also the id of the last one.
+    pub(crate) fn input_clause_count(&self) -> u64 {
+        (self.clause_ranges.len() + self.tautologies) as u64
+    }
+    /// Checks that `model` satisfies every clause, returning the first one
+    /// it doesn't. The model holds one literal per variable, ordered by
+    /// variable as returned by [`Solver::model`](crate::solver::Solver::model),
+    /// variables past its end count as unassigned.
+    pub fn evaluate(&self, model: &[Lit]) -> Result<(), UnsatisfiedClause> {
+        for (id, clause) in self.iter_with_ids() {
+            if !clause.iter().any(|lit| model.get(lit.index()) == Some(lit)) {
+                return Err(UnsatisfiedClause {
+                    id,
+                    clause: clause.to_vec(),
+                });
+            }
+        }
+        Ok(())
+    }
+    /// Adds a clause, silently dropping tautologies and duplicate literals,
+    /// see [`Self::try_add_clause`].
     pub fn add_clause(&mut self, clause: &[Lit]) {
-        if let Ok(clause) = self.check_clause(clause) {
-            let start = self.literals.len();
-            self.literals.extend_from_slice(&clause);
-            let end = self.literals.len();
-            self.clause_ranges.push(start..end);
+        self.try_add_clause(clause);
+    }
+    /// Adds a clause and reports whether it was dropped as a tautology or
+    /// had duplicate literals removed, which usually points at a bug in the
+    /// encoding that produced it. Both are counted, see
+    /// [`Self::tautologies`] and [`Self::duplicate_literals`].
+    pub fn try_add_clause(&mut self, clause: &[Lit]) -> AddClauseOutcome {
+        let Some(clause_lits) = self.check_clause(clause) else {
+            self.tautologies += 1;
+            return AddClauseOutcome::Tautology;
         };
+        self.clause_ids.push(self.input_clause_count() + 1);
+        let start = self.literals.len();
+        self.literals.extend_from_slice(&clause_lits);
+        let end = self.literals.len();
+        self.clause_ranges.push(start..end);
+        if let Some(max) = clause_lits.iter().map(|lit| lit.index()).max() {
+            self.declare_vars(max + 1);
+        }
+        let duplicates = clause.len() - clause_lits.len();
+        if duplicates == 0 {
+            AddClauseOutcome::Added
+        } else {
+            self.duplicate_literals += duplicates;
+            AddClauseOutcome::Simplified { duplicates }
+        }
+    }
+    /// Number of tautologies dropped by [`Self::try_add_clause`].
+    pub fn tautologies(&self) -> usize {
+        self.tautologies
+    }
+    /// Number of duplicate literals removed by [`Self::try_add_clause`].
+    pub fn duplicate_literals(&self) -> usize {
+        self.duplicate_literals
     }
+    /// Removes duplicate literals, `None` for tautologies.
     #[inline]
-    fn check_clause(&mut self, clause: &[Lit]) -> Result<Vec<Lit>> {
+    fn check_clause(&self, clause: &[Lit]) -> Option<Vec<Lit>> {
         let mut check = HashSet::<Lit>::new();
         let mut res = Vec::new();
         for ele in clause {
             if check.contains(&!*ele) {
-                bail!("this clause has a and !a , no sense");
+                return None;
             }
             if !check.contains(ele) {
                 check.insert(*ele);
-                self.max_lit_index = ele.index().max(self.max_lit_index);
                 res.push(*ele);
             }
         }
-        Ok(res)
+        Some(res)
     }
-    #[inline]
-    pub(super) fn get_max_lit_index(&self) -> usize {
-        self.max_lit_index
+}
+
+/// Parses a formula in DIMACS CNF format.
+///
+/// The `p cnf <vars> <clauses>` header must come before the first clause, and
+/// every literal and the clause count are checked against it. Comments start
+/// with `c` and run to the end of the line, they may appear on any line.
+/// Clauses are terminated by `0` and may span several lines. A line starting
+/// with `%` ends the formula, as in the SATLIB benchmark files.
+pub fn parse_dimacs<R: BufRead>(mut reader: R) -> Result<CnfFormula, DimacsError> {
+    let mut formula = CnfFormula::new();
+    let mut header: Option<(usize, usize)> = None;
+    let mut clause = Vec::new();
+    let mut clause_count = 0;
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+        let mut tokens = tokens(&line);
+        let Some((column, first)) = tokens.next() else {
+            continue;
+        };
+        match first {
+            "%" => break,
+            "p" => {
+                if header.is_some() {
+                    return Err(DimacsError::DuplicateHeader {
+                        line: line_number,
+                        column,
+                    });
+                }
+                if clause_count != 0 || !clause.is_empty() {
+                    return Err(DimacsError::InvalidHeader {
+                        line: line_number,
+                        column,
+                    });
+                }
+                let (var_count, clause_count) = parse_header(tokens, line_number, column)?;
+                formula.declare_vars(var_count);
+                header = Some((var_count, clause_count));
+                continue;
+            }
+            _ => {}
+        }
+        for (column, token) in once((column, first)).chain(tokens) {
+            if token.starts_with('c') {
+                break;
+            }
+            let Some((var_count, _)) = header else {
+                return Err(DimacsError::MissingHeader);
+            };
+            let value = token
+                .parse::<isize>()
+                .map_err(|_| DimacsError::InvalidLiteral {
+                    line: line_number,
+                    column,
+                    token: token.to_string(),
+                })?;
+            if value == 0 {
+                formula.add_clause(&clause);
+                clause.clear();
+                clause_count += 1;
+                continue;
+            }
+            let var = value.unsigned_abs();
+            if var > var_count {
+                return Err(DimacsError::VarOutOfRange {
+                    line: line_number,
+                    column,
+                    var,
+                    var_count,
+                });
+            }
+            clause.push(Lit::from_dimacs(var, value > 0));
+        }
+    }
+    let Some((_, expected)) = header else {
+        return Err(DimacsError::MissingHeader);
+    };
+    if !clause.is_empty() {
+        return Err(DimacsError::UnterminatedClause { line: line_number });
+    }
+    if clause_count != expected {
+        return Err(DimacsError::ClauseCountMismatch {
+            expected,
+            found: clause_count,
+        });
     }
+    Ok(formula)
+}
+fn parse_header<'a>(
+    mut tokens: impl Iterator<Item = (usize, &'a str)>,
+    line: usize,
+    column: usize,
+) -> Result<(usize, usize), DimacsError> {
+    let invalid = |column| DimacsError::InvalidHeader { line, column };
+    match tokens.next() {
+        Some((_, "cnf")) => {}
+        Some((column, _)) => return Err(invalid(column)),
+        None => return Err(invalid(column)),
+    }
+    let mut numbers = [0; 2];
+    for (i, number) in numbers.iter_mut().enumerate() {
+        let Some((column, token)) = tokens.next() else {
+            return Err(invalid(column));
+        };
+        *number = token.parse().map_err(|_| invalid(column))?;
+        // more variables than a literal can hold
+        if i == 0 && *number > Lit::MAX_VAR {
+            return Err(invalid(column));
+        }
+    }
+    match tokens.next() {
+        Some((column, token)) if !token.starts_with('c') => Err(invalid(column)),
+        _ => Ok((numbers[0], numbers[1])),
+    }
+}
+pub(crate) fn write_dimacs_header<W: Write>(
+    writer: &mut W,
+    var_count: usize,
+    clause_count: usize,
+) -> io::Result<()> {
+    writeln!(writer, "p cnf {} {}", var_count, clause_count)
+}
+pub(crate) fn write_dimacs_clause<W: Write>(writer: &mut W, clause: &[Lit]) -> io::Result<()> {
+    for lit in clause {
+        if lit.is_negative() {
+            write!(writer, "-")?;
+        }
+        write!(writer, "{} ", lit.to_dimacs())?;
+    }
+    writeln!(writer, "0")
+}
+/// Splits a line on whitespace, yielding each token with its 1-based column.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    let start = line.as_ptr() as usize;
+    line.split_ascii_whitespace()
+        .map(move |token| (token.as_ptr() as usize - start + 1, token))
 }
@@ -0,0 +1,84 @@
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+
+use crate::error::DimacsError;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+const BZIP2_MAGIC: &[u8] = b"BZh";
+/// Enough bytes to tell every supported format apart.
+const MAGIC_LEN: usize = XZ_MAGIC.len();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Compression {
+    None,
+    Gzip,
+    Xz,
+    Bzip2,
+}
+impl Compression {
+    /// Detects the compression format from the first bytes of the input.
+    pub(crate) fn detect(header: &[u8]) -> Self {
+        if header.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if header.starts_with(XZ_MAGIC) {
+            Compression::Xz
+        } else if header.starts_with(BZIP2_MAGIC) {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+    fn name(self) -> &'static str {
+        match self {
+            Compression::None => "plain",
+            Compression::Gzip => "gzip",
+            Compression::Xz => "xz",
+            Compression::Bzip2 => "bzip2",
+        }
+    }
+}
+
+/// Wraps `reader` in a streaming decoder if it starts with the magic bytes of
+/// a supported compression format, otherwise it is returned as is.
+pub(crate) fn decompress<'a, R: Read + 'a>(
+    reader: R,
+) -> Result<Box<dyn BufRead + 'a>, DimacsError> {
+    let mut reader = reader;
+    let header = read_header(&mut reader)?;
+    let compression = Compression::detect(&header);
+    let reader = BufReader::new(Cursor::new(header).chain(reader));
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(reader),
+        ))),
+        #[cfg(feature = "xz")]
+        Compression::Xz => Ok(Box::new(BufReader::new(
+            xz2::bufread::XzDecoder::new_multi_decoder(reader),
+        ))),
+        #[cfg(feature = "bzip2")]
+        Compression::Bzip2 => Ok(Box::new(BufReader::new(
+            bzip2::bufread::MultiBzDecoder::new(reader),
+        ))),
+        #[allow(unreachable_patterns)]
+        unsupported => Err(DimacsError::UnsupportedCompression(unsupported.name())),
+    }
+}
+
+/// Reads the first [`MAGIC_LEN`] bytes, or fewer at the end of the input. A
+/// pipe may return less than that from a single read.
+fn read_header<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut header = vec![0; MAGIC_LEN];
+    let mut len = 0;
+    while len < MAGIC_LEN {
+        match reader.read(&mut header[len..]) {
+            Ok(0) => break,
+            Ok(read) => len += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    header.truncate(len);
+    Ok(header)
+}
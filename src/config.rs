@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use crate::error::RsatError;
+
+/// How the polarity of a decision variable is picked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PolarityMode {
+    /// Always decide the variable to be true.
+    True,
+    /// Always decide the variable to be false.
+    False,
+    /// Pick a random polarity, seeded by [`SolverConfig::random_seed`].
+    Random,
+    /// Phase saving: reuse the value the variable had before it was last
+    /// unassigned by backtracking, false if it was never assigned.
+    #[default]
+    Saved,
+    /// Prefer the phases of the largest conflict-free trail seen since the
+    /// last rephasing, falling back to the saved phase, as in CaDiCaL. The
+    /// phases are periodically reset to the best trail so far, the original
+    /// or the inverted phases to move the search somewhere else.
+    Target,
+}
+/// When the solver abandons the current assignment and restarts the search
+/// from decision level 0. Learnt clauses, activities and saved phases are
+/// kept across restarts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestartPolicy {
+    Never,
+    /// Restart every `interval` conflicts.
+    Fixed {
+        interval: u64,
+    },
+    /// Restart after `initial` conflicts, growing the interval by `factor`
+    /// after every restart.
+    Geometric {
+        initial: u64,
+        factor: f64,
+    },
+    /// Restart after `unit` times the next element of the Luby sequence
+    /// 1, 1, 2, 1, 1, 2, 4, ... conflicts.
+    Luby {
+        unit: u64,
+    },
+    /// Restart when the fast moving average of the learnt clauses' LBD is
+    /// more than `margin` times the slow one, and at least `min_conflicts`
+    /// conflicts happened since the last restart, as in glucose.
+    Glucose {
+        margin: f64,
+        min_conflicts: u64,
+    },
+}
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy::Glucose {
+            margin: 1.25,
+            min_conflicts: 2,
+        }
+    }
+}
+/// How learnt clauses are shortened after conflict analysis.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MinimizeMode {
+    None,
+    /// Remove literals whose reason only contains literals of the clause.
+    Basic,
+    /// Remove literals implied by the clause through any chain of reasons,
+    /// as in MiniSat.
+    #[default]
+    Recursive,
+}
+/// Budgets for a single call to solve, the solver gives up with
+/// [`SolveState::Unknown`](crate::solver::SolveState::Unknown) once any of
+/// them is used up. `None` means unlimited.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub conflicts: Option<u64>,
+    pub decisions: Option<u64>,
+    /// Number of assigned literals whose consequences were propagated.
+    pub propagations: Option<u64>,
+    /// Wall-clock time, only checked every few conflicts or decisions.
+    pub timeout: Option<Duration>,
+}
+#[derive(Debug, Clone)]
+pub struct SolverConfig {
+    pub polarity: PolarityMode,
+    pub random_seed: u64,
+    pub restart: RestartPolicy,
+    /// Conflicts until the first reduction of the learnt clause database, the
+    /// interval grows by this much after every reduction. 0 keeps every
+    /// learnt clause.
+    pub reduce_interval: u64,
+    pub minimize: MinimizeMode,
+    /// Keeps a copy of the added clauses and checks every model against it,
+    /// the first falsified clause is reported by
+    /// [`Solver::verify_error`](crate::solver::Solver::verify_error). Meant
+    /// for debugging.
+    pub verify_model: bool,
+    pub limits: Limits,
+}
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            polarity: PolarityMode::default(),
+            random_seed: 0x2545_f491_4f6c_dd1d,
+            restart: RestartPolicy::default(),
+            reduce_interval: 2000,
+            minimize: MinimizeMode::default(),
+            verify_model: false,
+            limits: Limits::default(),
+        }
+    }
+}
+impl SolverConfig {
+    /// Checks that the restart policy's parameters make sense, e.g. that
+    /// geometric restarts don't shrink their interval.
+    pub fn validate(&self) -> Result<(), RsatError> {
+        let invalid = |reason: &str| Err(RsatError::InvalidConfig(reason.to_string()));
+        match self.restart {
+            RestartPolicy::Fixed { interval: 0 } => invalid("fixed restart interval is 0"),
+            RestartPolicy::Geometric { initial: 0, .. } => {
+                invalid("initial geometric restart interval is 0")
+            }
+            RestartPolicy::Geometric { factor, .. } if !(factor >= 1.0 && factor.is_finite()) => {
+                invalid("geometric restart factor must be finite and at least 1")
+            }
+            RestartPolicy::Luby { unit: 0 } => invalid("luby restart unit is 0"),
+            RestartPolicy::Glucose { margin, .. } if !(margin > 0.0 && margin.is_finite()) => {
+                invalid("glucose restart margin must be finite and positive")
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,34 +1,258 @@
-use crate::{cnf::CnfFormula, lit::Lit, solver::ClauseRef};
+use std::{
+    io::{self, Write},
+    ops::{Deref, DerefMut},
+};
+
+use crate::{
+    cnf::{write_dimacs_clause, write_dimacs_header},
+    graph::PropReason,
+    lit::Lit,
+    solver::{ClauseRef, Solver},
+};
+
+/// Learnt clauses with at most this LBD are kept forever.
+const CORE_LBD: usize = 2;
+/// Learnt clauses with at most this LBD survive reductions while they keep
+/// being used in conflicts.
+const TIER2_LBD: usize = 6;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Clause {
+    lits: Vec<Lit>,
+    pub(crate) id: u64,
+    pub(crate) learnt: bool,
+    /// Lowest number of distinct decision levels of the clause seen so far,
+    /// when it was learnt or used in a conflict. Only tracked for learnt
+    /// clauses.
+    pub(crate) lbd: usize,
+    /// Whether the clause took part in a conflict since the last reduction.
+    pub(crate) used: bool,
+}
+impl Deref for Clause {
+    type Target = [Lit];
+
+    fn deref(&self) -> &Self::Target {
+        &self.lits
+    }
+}
+impl DerefMut for Clause {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.lits
+    }
+}
 
 #[derive(Debug, Default)]
 pub(crate) struct ClauseDb {
+    pub(crate) has_empty_clause: bool,
     pub(crate) assign_clauses: Vec<Lit>,
     pub(crate) binary_clauses: Vec<[Lit; 2]>,
-    pub(crate) long_clauses: Vec<Vec<Lit>>,
+    /// Clause ids of `binary_clauses`.
+    pub(crate) binary_ids: Vec<u64>,
+    pub(crate) long_clauses: Vec<Clause>,
+    /// Clauses are numbered from 1 in the order they are added, learnt
+    /// clauses included, as the ids of an LRAT proof.
+    last_id: u64,
+    reduce_interval: u64,
+    conflicts_until_reduce: u64,
+    reductions: u64,
 }
 impl ClauseDb {
+    pub(crate) fn new(reduce_interval: u64) -> Self {
+        ClauseDb {
+            reduce_interval,
+            conflicts_until_reduce: reduce_interval,
+            ..Default::default()
+        }
+    }
+    /// Reserves the next clause id.
     #[inline]
-    pub(crate) fn add_formula(&mut self, formula: &CnfFormula) {
-        for clause in formula.iter() {
-            self.add_clause(clause);
+    pub(crate) fn new_id(&mut self) -> u64 {
+        self.last_id += 1;
+        self.last_id
+    }
+    /// Uses up the ids up to `last_id` for clauses that were dropped.
+    pub(crate) fn skip_ids(&mut self, last_id: u64) {
+        self.last_id = self.last_id.max(last_id);
+    }
+    /// The id of the clause added last.
+    #[inline]
+    pub(crate) fn last_id(&self) -> u64 {
+        self.last_id
+    }
+    #[inline]
+    pub(crate) fn id(&self, clause_ref: ClauseRef) -> u64 {
+        match clause_ref {
+            ClauseRef::Binary(index) => self.binary_ids[index],
+            ClauseRef::Long(index) => self.long_clauses[index].id,
         }
     }
     #[inline]
     pub(crate) fn add_clause(&mut self, clause: &[Lit]) -> Option<ClauseRef> {
+        self.push_clause(clause, None)
+    }
+    /// Adds a clause learnt from a conflict. Learnt unit and binary clauses
+    /// are never deleted, so only long ones are tagged.
+    #[inline]
+    pub(crate) fn add_learnt_clause(&mut self, clause: &[Lit], lbd: usize) -> Option<ClauseRef> {
+        self.push_clause(clause, Some(lbd))
+    }
+    fn push_clause(&mut self, clause: &[Lit], learnt_lbd: Option<usize>) -> Option<ClauseRef> {
+        let id = self.new_id();
         match clause {
-            [] => None,
+            [] => {
+                self.has_empty_clause = true;
+                None
+            }
             [lit] => {
                 self.assign_clauses.push(*lit);
                 None
             }
             [m, n] => {
                 self.binary_clauses.push([*m, *n]);
+                self.binary_ids.push(id);
                 Some(ClauseRef::Binary(self.binary_clauses.len() - 1))
             }
             _ => {
-                self.long_clauses.push(clause.to_vec());
+                self.long_clauses.push(Clause {
+                    lits: clause.to_vec(),
+                    id,
+                    learnt: learnt_lbd.is_some(),
+                    lbd: learnt_lbd.unwrap_or(0),
+                    used: false,
+                });
                 Some(ClauseRef::Long(self.long_clauses.len() - 1))
             }
         }
     }
+    /// Called once per conflict, returns whether it's time to reduce the
+    /// learnt clauses.
+    #[inline]
+    pub(crate) fn on_conflict(&mut self) -> bool {
+        if self.reduce_interval == 0 {
+            return false;
+        }
+        self.conflicts_until_reduce = self.conflicts_until_reduce.saturating_sub(1);
+        self.conflicts_until_reduce == 0
+    }
+    /// Marks a long clause as used in conflict analysis and keeps the lower of
+    /// its old and current LBD.
+    #[inline]
+    pub(crate) fn mark_used(&mut self, index: usize, lbd: usize) {
+        let clause = &mut self.long_clauses[index];
+        if clause.learnt {
+            clause.used = true;
+            clause.lbd = clause.lbd.min(lbd);
+        }
+    }
+    /// Picks the learnt clauses to delete. Core clauses are always kept, tier2
+    /// clauses as long as they were used since the last reduction, and the
+    /// worse half of the remaining clauses is deleted, preferring unused and
+    /// high LBD ones. Clauses that are currently a reason are locked.
+    fn select_deleted(&mut self, locked: &[bool]) -> Vec<bool> {
+        let mut candidates: Vec<usize> = (0..self.long_clauses.len())
+            .filter(|&index| {
+                let clause = &self.long_clauses[index];
+                clause.learnt
+                    && clause.lbd > CORE_LBD
+                    && !(clause.lbd <= TIER2_LBD && clause.used)
+                    && !locked[index]
+            })
+            .collect();
+        candidates.sort_by_key(|&index| {
+            let clause = &self.long_clauses[index];
+            (clause.used, std::cmp::Reverse(clause.lbd))
+        });
+        let mut deleted = vec![false; self.long_clauses.len()];
+        for &index in &candidates[..candidates.len() / 2] {
+            deleted[index] = true;
+        }
+        for clause in self.long_clauses.iter_mut() {
+            clause.used = false;
+        }
+        deleted
+    }
+    /// Removes the deleted clauses, returning the new index of every old
+    /// index, `None` for the deleted ones.
+    fn compact(&mut self, deleted: &[bool]) -> Vec<Option<usize>> {
+        let mut remap = Vec::with_capacity(self.long_clauses.len());
+        let mut kept = 0;
+        for &is_deleted in deleted {
+            if is_deleted {
+                remap.push(None);
+            } else {
+                remap.push(Some(kept));
+                kept += 1;
+            }
+        }
+        let mut deleted = deleted.iter();
+        self.long_clauses.retain(|_| !deleted.next().unwrap());
+        remap
+    }
+    pub(crate) fn write_dimacs<W: Write>(
+        &self,
+        writer: &mut W,
+        var_count: usize,
+    ) -> io::Result<()> {
+        let clause_count = self.has_empty_clause as usize
+            + self.assign_clauses.len()
+            + self.binary_clauses.len()
+            + self.long_clauses.len();
+        write_dimacs_header(writer, var_count, clause_count)?;
+        if self.has_empty_clause {
+            write_dimacs_clause(writer, &[])?;
+        }
+        for lit in &self.assign_clauses {
+            write_dimacs_clause(writer, &[*lit])?;
+        }
+        for clause in &self.binary_clauses {
+            write_dimacs_clause(writer, clause)?;
+        }
+        for clause in &self.long_clauses {
+            write_dimacs_clause(writer, clause)?;
+        }
+        writer.flush()
+    }
+}
+impl Solver {
+    /// Deletes about half of the learnt clauses that aren't worth keeping and
+    /// schedules the next reduction.
+    pub(crate) fn reduce_db(&mut self) {
+        let mut locked = vec![false; self.clause_db.long_clauses.len()];
+        for lit in self.prop_queue.iter() {
+            if let PropReason::Long(ClauseRef::Long(index)) = self.prop_graph.get_node(lit).reason {
+                locked[index] = true;
+            }
+        }
+        let deleted = self.clause_db.select_deleted(&locked);
+        self.stats.reductions += 1;
+        let count = deleted.iter().filter(|deleted| **deleted).count();
+        self.stats.deleted_clauses += count as u64;
+        debug!(
+            "reduce the clause database, deleting {} learnt clauses",
+            count
+        );
+        if let Some(proof) = &mut self.proof {
+            for (clause, _) in self
+                .clause_db
+                .long_clauses
+                .iter()
+                .zip(&deleted)
+                .filter(|(_, deleted)| **deleted)
+            {
+                proof.delete_clause(clause.id, clause);
+            }
+        }
+        let remap = self.clause_db.compact(&deleted);
+        self.watch_lists.remap_long(&remap);
+        for lit in self.prop_queue.iter() {
+            let node = self.prop_graph.get_node_mut(lit);
+            if let PropReason::Long(ClauseRef::Long(index)) = node.reason {
+                // locked clauses are never deleted
+                node.reason = PropReason::Long(ClauseRef::Long(remap[index].unwrap()));
+            }
+        }
+        let db = &mut self.clause_db;
+        db.reductions += 1;
+        db.conflicts_until_reduce = db.reduce_interval * (db.reductions + 1);
+    }
 }
@@ -1 +1,225 @@
+use std::{error, fmt, io};
 
+use crate::lit::Lit;
+
+#[derive(Debug)]
+pub enum DimacsError {
+    Io(io::Error),
+    UnsupportedCompression(&'static str),
+    MissingHeader,
+    InvalidHeader {
+        line: usize,
+        column: usize,
+    },
+    DuplicateHeader {
+        line: usize,
+        column: usize,
+    },
+    InvalidLiteral {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    VarOutOfRange {
+        line: usize,
+        column: usize,
+        var: usize,
+        var_count: usize,
+    },
+    UnterminatedClause {
+        line: usize,
+    },
+    ClauseCountMismatch {
+        expected: usize,
+        found: usize,
+    },
+}
+impl fmt::Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DimacsError::Io(e) => write!(f, "io error while reading dimacs: {}", e),
+            DimacsError::UnsupportedCompression(format) => write!(
+                f,
+                "input is {} compressed but rsat was built without {} support",
+                format, format
+            ),
+            DimacsError::MissingHeader => write!(f, "missing `p cnf <vars> <clauses>` header"),
+            DimacsError::InvalidHeader { line, column } => {
+                write!(
+                    f,
+                    "{}:{}: invalid `p cnf <vars> <clauses>` header",
+                    line, column
+                )
+            }
+            DimacsError::DuplicateHeader { line, column } => {
+                write!(f, "{}:{}: duplicate `p` header", line, column)
+            }
+            DimacsError::InvalidLiteral {
+                line,
+                column,
+                token,
+            } => write!(f, "{}:{}: invalid literal `{}`", line, column, token),
+            DimacsError::VarOutOfRange {
+                line,
+                column,
+                var,
+                var_count,
+            } => write!(
+                f,
+                "{}:{}: variable {} is out of range, header declares {} variables",
+                line, column, var, var_count
+            ),
+            DimacsError::UnterminatedClause { line } => {
+                write!(f, "{}: last clause is not terminated by `0`", line)
+            }
+            DimacsError::ClauseCountMismatch { expected, found } => write!(
+                f,
+                "header declares {} clauses but {} were found",
+                expected, found
+            ),
+        }
+    }
+}
+impl error::Error for DimacsError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DimacsError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for DimacsError {
+    fn from(value: io::Error) -> Self {
+        DimacsError::Io(value)
+    }
+}
+
+#[derive(Debug)]
+pub enum ProofError {
+    Io(io::Error),
+    InvalidStep { step: usize },
+    NotImplied { step: usize },
+    UnknownClause { step: usize },
+    MissingEmptyClause,
+}
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::Io(e) => write!(f, "io error while reading proof: {}", e),
+            ProofError::InvalidStep { step } => write!(f, "proof step {}: malformed step", step),
+            ProofError::NotImplied { step } => {
+                write!(f, "proof step {}: lemma is not implied", step)
+            }
+            ProofError::UnknownClause { step } => write!(
+                f,
+                "proof step {}: refers to a clause that does not exist",
+                step
+            ),
+            ProofError::MissingEmptyClause => write!(f, "proof does not derive the empty clause"),
+        }
+    }
+}
+impl error::Error for ProofError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ProofError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for ProofError {
+    fn from(value: io::Error) -> Self {
+        ProofError::Io(value)
+    }
+}
+
+/// A clause falsified by a model, see
+/// [`CnfFormula::evaluate`](crate::cnf::CnfFormula::evaluate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedClause {
+    /// Position of the clause in the input counting from 1, tautologies
+    /// included, as in [`CnfFormula::iter_with_ids`](crate::cnf::CnfFormula::iter_with_ids).
+    pub id: u64,
+    pub clause: Vec<Lit>,
+}
+impl fmt::Display for UnsatisfiedClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "clause {} `", self.id)?;
+        for lit in &self.clause {
+            if lit.is_negative() {
+                write!(f, "-")?;
+            }
+            write!(f, "{} ", lit.to_dimacs())?;
+        }
+        write!(f, "0` is not satisfied by the model")
+    }
+}
+impl error::Error for UnsatisfiedClause {}
+
+/// Any error returned by the library, so callers can handle every failure
+/// with one type.
+#[derive(Debug)]
+pub enum RsatError {
+    Dimacs(DimacsError),
+    /// A variable outside of the range `1..=max_var`.
+    VarOutOfRange {
+        var: usize,
+        max_var: usize,
+    },
+    InvalidConfig(String),
+    Proof(ProofError),
+    /// Writing the proof failed.
+    ProofIo(io::Error),
+    /// An LRAT proof was started after clauses were added to the solver.
+    LateLratProof,
+    /// The model found falsifies an added clause, only checked with
+    /// [`SolverConfig::verify_model`](crate::config::SolverConfig::verify_model).
+    InvalidModel(UnsatisfiedClause),
+    /// Solving stopped at one of the configured
+    /// [`Limits`](crate::config::Limits) or an interrupt before deciding the
+    /// formula.
+    BudgetExhausted,
+}
+impl fmt::Display for RsatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RsatError::Dimacs(e) => e.fmt(f),
+            RsatError::VarOutOfRange { var, max_var } => write!(
+                f,
+                "variable {} is out of range, variables go from 1 to {}",
+                var, max_var
+            ),
+            RsatError::InvalidConfig(reason) => write!(f, "invalid solver config: {}", reason),
+            RsatError::Proof(e) => e.fmt(f),
+            RsatError::ProofIo(e) => write!(f, "io error while writing proof: {}", e),
+            RsatError::LateLratProof => {
+                write!(f, "an LRAT proof must be started before adding clauses")
+            }
+            RsatError::InvalidModel(clause) => write!(f, "model verification failed: {}", clause),
+            RsatError::BudgetExhausted => {
+                write!(f, "solving stopped before the formula was decided")
+            }
+        }
+    }
+}
+impl error::Error for RsatError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            RsatError::Dimacs(e) => Some(e),
+            RsatError::Proof(e) => Some(e),
+            RsatError::ProofIo(e) => Some(e),
+            RsatError::InvalidModel(clause) => Some(clause),
+            _ => None,
+        }
+    }
+}
+impl From<DimacsError> for RsatError {
+    fn from(value: DimacsError) -> Self {
+        RsatError::Dimacs(value)
+    }
+}
+impl From<ProofError> for RsatError {
+    fn from(value: ProofError) -> Self {
+        RsatError::Proof(value)
+    }
+}
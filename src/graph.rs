@@ -1,10 +1,38 @@
-use crate::{lit::Lit, solver::ClauseRef};
+use crate::{db::ClauseDb, lit::Lit, solver::ClauseRef};
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum PropReason {
     Unit,
-    Binary([Lit;1]),
+    /// The other literal of the binary clause, and the clause's index in
+    /// `ClauseDb::binary_clauses`.
+    Binary([Lit; 1], usize),
     Long(ClauseRef),
 }
+impl PropReason {
+    /// The other literals of the reason clause, all of them false.
+    #[inline]
+    pub(crate) fn lits<'a>(&'a self, clause_db: &'a ClauseDb) -> &'a [Lit] {
+        match self {
+            PropReason::Unit => &[],
+            PropReason::Binary(block, _) => block,
+            PropReason::Long(clause_ref) => match clause_ref {
+                // binary reasons are always `PropReason::Binary`, see
+                // `Solver::reason_of`, an empty reason here would make every
+                // literal it implies look redundant to minimization
+                ClauseRef::Binary(_) => unreachable!("binary clause stored as a long reason"),
+                ClauseRef::Long(index) => &clause_db.long_clauses[*index][1..],
+            },
+        }
+    }
+    /// The id of the reason clause, `None` for decisions and level 0 units.
+    #[inline]
+    pub(crate) fn id(&self, clause_db: &ClauseDb) -> Option<u64> {
+        match *self {
+            PropReason::Unit => None,
+            PropReason::Binary(_, index) => Some(clause_db.id(ClauseRef::Binary(index))),
+            PropReason::Long(clause_ref) => Some(clause_db.id(clause_ref)),
+        }
+    }
+}
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct PropNode {
     pub(crate) reason: PropReason,
@@ -29,6 +57,10 @@ impl PropGraph {
         node.level = level;
     }
     #[inline]
+    pub(crate) fn get_node_mut(&mut self, lit: &Lit) -> &mut PropNode {
+        &mut self.0[lit.index()]
+    }
+    #[inline]
     pub(crate) fn get_node(&self, lit: &Lit) -> &PropNode {
         debug_assert!(lit.index() < self.0.len());
         &self.0[lit.index()]
@@ -1,17 +1,27 @@
 pub fn add(left: usize, right: usize) -> usize {
     left + right
 }
+#[macro_use]
+mod trace;
 mod assign;
 pub mod cnf;
+mod compress;
+pub mod config;
 mod db;
-// mod error;
+pub mod error;
 mod graph;
 pub mod lit;
 pub mod solver;
+pub mod stats;
 // mod test;
 mod cdcl;
 mod decision;
+mod limit;
+mod phase;
+pub mod proof;
 mod prop;
+mod restart;
+mod vsids;
 mod watch;
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,104 @@
+use std::time::Instant;
+
+use crate::{config::Limits, stats::SolverStats};
+
+/// Checking the clock is much slower than counting, so it's only done once
+/// every this many calls to [`Budget::exhausted`], and only one call in this
+/// many of a phase of the search is timed.
+pub(crate) const CLOCK_INTERVAL: u32 = 64;
+
+/// Tracks what a call to solve has used up of its [`Limits`].
+#[derive(Debug)]
+pub(crate) struct Budget {
+    limits: Limits,
+    /// The statistics when the call started.
+    conflicts: u64,
+    decisions: u64,
+    propagations: u64,
+    deadline: Option<Instant>,
+    ticks: u32,
+}
+impl Budget {
+    pub(crate) fn new(limits: Limits) -> Self {
+        Budget {
+            limits,
+            conflicts: 0,
+            decisions: 0,
+            propagations: 0,
+            deadline: None,
+            ticks: 0,
+        }
+    }
+    /// Resets the budget at the start of a call to solve.
+    pub(crate) fn start(&mut self, stats: &SolverStats) {
+        self.conflicts = stats.conflicts;
+        self.decisions = stats.decisions;
+        self.propagations = stats.propagations;
+        self.deadline = self.limits.timeout.map(|timeout| Instant::now() + timeout);
+        self.ticks = 0;
+    }
+    pub(crate) fn exhausted(&mut self, stats: &SolverStats) -> bool {
+        let over = |used: u64, limit: Option<u64>| limit.is_some_and(|limit| used >= limit);
+        if over(stats.conflicts - self.conflicts, self.limits.conflicts)
+            || over(stats.decisions - self.decisions, self.limits.decisions)
+            || over(
+                stats.propagations - self.propagations,
+                self.limits.propagations,
+            )
+        {
+            return true;
+        }
+        let Some(deadline) = self.deadline else {
+            return false;
+        };
+        self.ticks = self.ticks.wrapping_add(1);
+        self.ticks % CLOCK_INTERVAL == 1 && Instant::now() >= deadline
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let mut budget = Budget::new(Limits {
+            conflicts: Some(2),
+            decisions: Some(3),
+            ..Default::default()
+        });
+        let mut stats = SolverStats {
+            conflicts: 10,
+            decisions: 10,
+            ..Default::default()
+        };
+        budget.start(&stats);
+        stats.conflicts += 1;
+        stats.decisions += 2;
+        assert!(!budget.exhausted(&stats));
+        stats.decisions += 1;
+        assert!(budget.exhausted(&stats));
+        budget.start(&stats);
+        stats.conflicts += 1;
+        assert!(!budget.exhausted(&stats));
+        stats.conflicts += 1;
+        assert!(budget.exhausted(&stats));
+    }
+    #[test]
+    fn test_timeout() {
+        let stats = SolverStats::default();
+        let mut budget = Budget::new(Limits {
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        });
+        budget.start(&stats);
+        assert!(budget.exhausted(&stats));
+        let mut budget = Budget::new(Limits {
+            timeout: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        });
+        budget.start(&stats);
+        assert!((0..1000).all(|_| !budget.exhausted(&stats)));
+    }
+}
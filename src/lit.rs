@@ -1,17 +1,64 @@
+// `LitCode` is `usize` with the `wide-literals` feature, so the casts to
+// `usize` are no-ops there
+#![allow(clippy::unnecessary_cast)]
+
 use std::{fmt, ops};
 
+use crate::error::RsatError;
+
+/// Storage of a literal, `u32` unless the `wide-literals` feature asks for
+/// more than 2^31 variables at twice the memory.
+#[cfg(not(feature = "wide-literals"))]
+type LitCode = u32;
+#[cfg(feature = "wide-literals")]
+type LitCode = usize;
+
+/// A variable and its polarity, stored as `2 * index + negative`.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Lit(usize);
+pub struct Lit(LitCode);
 impl Lit {
+    /// The largest variable index a literal can hold.
+    pub const MAX_INDEX: usize = (LitCode::MAX >> 1) as usize;
+    /// The largest variable a literal can hold, in DIMACS numbering.
+    pub const MAX_VAR: usize = Self::MAX_INDEX + 1;
+    /// Like [`Self::from_dimacs`], but fails with
+    /// [`RsatError::VarOutOfRange`] instead of overflowing for variables
+    /// outside of `1..=MAX_VAR`.
+    pub fn try_from_dimacs(number: usize, polarity: bool) -> Result<Self, RsatError> {
+        if (1..=Self::MAX_VAR).contains(&number) {
+            Ok(Self::from_dimacs(number, polarity))
+        } else {
+            Err(RsatError::VarOutOfRange {
+                var: number,
+                max_var: Self::MAX_VAR,
+            })
+        }
+    }
     #[inline]
     pub fn from_dimacs(number: usize, polarity: bool) -> Self {
         debug_assert!(number >= 1);
         Self::from_index(number - 1, polarity)
     }
+    /// The literal of `var` that is true when the variable has the value
+    /// `polarity`, the same as `var.lit(polarity)`.
+    #[inline]
+    pub fn from_var(var: Var, polarity: bool) -> Self {
+        var.lit(polarity)
+    }
+    /// Variable indices are only used inside the crate, the public API takes
+    /// a [`Var`] so they can't be mixed up with literal codes.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is above `MAX_INDEX`.
     #[inline]
-    pub fn from_index(number: usize, polarity: bool) -> Self {
-        debug_assert!(number <= (usize::MAX >> 2));
-        Self(number << 1 | (!polarity as usize))
+    pub(crate) fn from_index(index: usize, polarity: bool) -> Self {
+        assert!(
+            index <= Self::MAX_INDEX,
+            "variable index {} is out of range",
+            index
+        );
+        Self((index as LitCode) << 1 | (!polarity as LitCode))
     }
     #[inline]
     pub fn is_negative(self) -> bool {
@@ -21,24 +68,27 @@ impl Lit {
     pub fn is_positive(self) -> bool {
         !self.is_negative()
     }
+    /// Index of the literal's variable, the same as `self.var().index()`.
     #[inline]
-    pub fn index(self) -> usize {
-        self.0 >> 1
+    pub(crate) fn index(self) -> usize {
+        (self.0 >> 1) as usize
+    }
+    #[inline]
+    pub fn var(self) -> Var {
+        Var(self.0 >> 1)
     }
     #[inline]
     pub(super) fn code(self) -> usize {
-        self.0
+        self.0 as usize
     }
     #[inline]
     pub fn to_dimacs(self) -> usize {
         self.index() + 1
     }
+    #[doc(hidden)]
     #[inline]
-    pub fn assign_bool(&self, assignment: &Vec<Option<bool>>) -> Option<bool> {
-        match assignment[self.index()] {
-            Some(assign) => Some(assign == self.is_positive()),
-            None => None,
-        }
+    pub fn assign_bool(&self, assignment: &[Option<bool>]) -> Option<bool> {
+        assignment[self.index()].map(|assign| assign == self.is_positive())
     }
 }
 impl ops::Not for Lit {
@@ -57,3 +107,57 @@ impl fmt::Debug for Lit {
         }
     }
 }
+
+/// A variable, numbered from 0 internally and from 1 in DIMACS.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Var(LitCode);
+impl Var {
+    #[inline]
+    pub fn from_dimacs(number: usize) -> Self {
+        debug_assert!(number >= 1);
+        Self::from_index(number - 1)
+    }
+    /// Like [`Self::from_index`], but fails with
+    /// [`RsatError::VarOutOfRange`] for indices above [`Lit::MAX_INDEX`].
+    pub fn try_from_index(index: usize) -> Result<Self, RsatError> {
+        if index <= Lit::MAX_INDEX {
+            Ok(Self::from_index(index))
+        } else {
+            Err(RsatError::VarOutOfRange {
+                var: index.saturating_add(1),
+                max_var: Lit::MAX_VAR,
+            })
+        }
+    }
+    /// # Panics
+    ///
+    /// If `index` is above [`Lit::MAX_INDEX`], see [`Self::try_from_index`].
+    #[inline]
+    pub fn from_index(index: usize) -> Self {
+        assert!(
+            index <= Lit::MAX_INDEX,
+            "variable index {} is out of range",
+            index
+        );
+        Self(index as LitCode)
+    }
+    /// The literal of this variable that is true when the variable has the
+    /// value `polarity`.
+    #[inline]
+    pub fn lit(self, polarity: bool) -> Lit {
+        Lit(self.0 << 1 | (!polarity as LitCode))
+    }
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+    #[inline]
+    pub fn to_dimacs(self) -> usize {
+        self.index() + 1
+    }
+}
+impl fmt::Debug for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_dimacs())
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{config::PolarityMode, lit::Lit};
+
+/// Conflicts between two rephasings grow linearly with this interval.
+const REPHASE_INTERVAL: u64 = 1000;
+
+#[derive(Debug)]
+pub(crate) struct Phases {
+    mode: PolarityMode,
+    saved: Vec<bool>,
+    target: Vec<bool>,
+    /// Trail length the target phases were taken from.
+    target_len: usize,
+    best: Vec<bool>,
+    best_len: usize,
+    rng: u64,
+    conflicts: u64,
+    next_rephase: u64,
+    rephase_count: u64,
+}
+impl Phases {
+    pub(crate) fn new(mode: PolarityMode, seed: u64) -> Self {
+        Phases {
+            mode,
+            saved: Vec::new(),
+            target: Vec::new(),
+            target_len: 0,
+            best: Vec::new(),
+            best_len: 0,
+            // xorshift gets stuck on zero
+            rng: seed.max(1),
+            conflicts: 0,
+            next_rephase: REPHASE_INTERVAL,
+            rephase_count: 0,
+        }
+    }
+    pub(crate) fn resize(&mut self, var_count: usize) {
+        self.saved.resize(var_count, false);
+        self.target.resize(var_count, false);
+        self.best.resize(var_count, false);
+    }
+    /// Remembers the polarity of a literal being unassigned.
+    #[inline]
+    pub(crate) fn save(&mut self, lit: &Lit) {
+        self.saved[lit.index()] = lit.is_positive();
+    }
+    /// The polarity to decide the variable `index` with.
+    #[inline]
+    pub(crate) fn decide(&mut self, index: usize) -> bool {
+        match self.mode {
+            PolarityMode::True => true,
+            PolarityMode::False => false,
+            PolarityMode::Random => self.next_random() & 1 == 1,
+            PolarityMode::Saved => self.saved[index],
+            PolarityMode::Target if self.target_len > 0 => self.target[index],
+            PolarityMode::Target => self.saved[index],
+        }
+    }
+    /// Called on every conflict with the part of the trail assigned before
+    /// the conflict level, which is known to be conflict-free.
+    pub(crate) fn on_conflict(&mut self, consistent_trail: &[Lit]) {
+        if self.mode != PolarityMode::Target {
+            return;
+        }
+        if consistent_trail.len() > self.target_len {
+            self.target_len = consistent_trail.len();
+            for lit in consistent_trail {
+                self.target[lit.index()] = lit.is_positive();
+            }
+        }
+        if consistent_trail.len() > self.best_len {
+            self.best_len = consistent_trail.len();
+            for lit in consistent_trail {
+                self.best[lit.index()] = lit.is_positive();
+            }
+        }
+        self.conflicts += 1;
+        if self.conflicts >= self.next_rephase {
+            self.rephase();
+        }
+    }
+    fn rephase(&mut self) {
+        match self.rephase_count % 4 {
+            0 | 2 => {
+                self.saved.copy_from_slice(&self.best);
+                self.best_len = 0;
+            }
+            1 => self.saved.fill(false),
+            _ => self.saved.fill(true),
+        }
+        self.target.copy_from_slice(&self.saved);
+        self.target_len = 0;
+        self.rephase_count += 1;
+        self.next_rephase = self.conflicts + REPHASE_INTERVAL * (self.rephase_count + 1);
+    }
+    #[inline]
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+}
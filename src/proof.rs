@@ -0,0 +1,268 @@
+use std::{
+    fmt,
+    io::{self, BufWriter, Write},
+    iter::once,
+};
+
+use crate::{error::RsatError, graph::PropReason, lit::Lit, solver::Solver};
+
+pub mod check;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofFormat {
+    /// Textual DRAT, one `1 -2 0` or `d 1 -2 0` line per step.
+    Drat,
+    /// Binary DRAT as understood by drat-trim, more compact and faster to
+    /// write and parse.
+    BinaryDrat,
+    /// Textual LRAT, every lemma is followed by the ids of the clauses that
+    /// derive it by unit propagation, so checking it needs no search.
+    Lrat,
+}
+
+/// Writes the clause additions and deletions of a proof to a sink.
+///
+/// The first write error stops the proof and is kept to be reported by
+/// [`Solver::finish_proof`], so solving itself never fails because of it.
+pub(crate) struct ProofWriter {
+    format: ProofFormat,
+    writer: BufWriter<Box<dyn Write + Send>>,
+    error: Option<io::Error>,
+    /// Id of the last added lemma, LRAT deletions are numbered with it.
+    last_id: u64,
+    /// Id of the unit clause asserting each variable assigned at level 0,
+    /// only kept for LRAT.
+    unit_ids: Vec<u64>,
+}
+impl fmt::Debug for ProofWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProofWriter")
+            .field("format", &self.format)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+impl ProofWriter {
+    pub(crate) fn new(writer: Box<dyn Write + Send>, format: ProofFormat) -> Self {
+        ProofWriter {
+            format,
+            writer: BufWriter::new(writer),
+            error: None,
+            last_id: 0,
+            unit_ids: Vec::new(),
+        }
+    }
+    #[inline]
+    pub(crate) fn is_lrat(&self) -> bool {
+        self.format == ProofFormat::Lrat
+    }
+    /// 0 if there is no unit clause for `var` yet.
+    #[inline]
+    pub(crate) fn unit_id(&self, var: usize) -> u64 {
+        self.unit_ids.get(var).copied().unwrap_or(0)
+    }
+    pub(crate) fn set_unit_id(&mut self, var: usize, id: u64) {
+        if var >= self.unit_ids.len() {
+            self.unit_ids.resize(var + 1, 0);
+        }
+        self.unit_ids[var] = id;
+    }
+    /// Adds a lemma, `hints` are only written to LRAT proofs.
+    #[inline]
+    pub(crate) fn add_clause(&mut self, id: u64, clause: &[Lit], hints: &[u64]) {
+        self.write_step(false, id, clause, hints);
+        self.last_id = id;
+    }
+    #[inline]
+    pub(crate) fn delete_clause(&mut self, id: u64, clause: &[Lit]) {
+        self.write_step(true, id, clause, &[]);
+    }
+    fn write_step(&mut self, delete: bool, id: u64, clause: &[Lit], hints: &[u64]) {
+        if self.error.is_some() {
+            return;
+        }
+        let writer = &mut self.writer;
+        let result = match self.format {
+            ProofFormat::Drat => write_text_step(writer, delete, clause),
+            ProofFormat::BinaryDrat => write_binary_step(writer, delete, clause),
+            ProofFormat::Lrat if delete => writeln!(writer, "{} d {} 0", self.last_id, id),
+            ProofFormat::Lrat => write_lrat_step(writer, id, clause, hints),
+        };
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.writer.flush(),
+        }
+    }
+}
+fn write_text_step<W: Write>(writer: &mut W, delete: bool, clause: &[Lit]) -> io::Result<()> {
+    if delete {
+        write!(writer, "d ")?;
+    }
+    for lit in clause {
+        if lit.is_negative() {
+            write!(writer, "-")?;
+        }
+        write!(writer, "{} ", lit.to_dimacs())?;
+    }
+    writeln!(writer, "0")
+}
+fn write_lrat_step<W: Write>(
+    writer: &mut W,
+    id: u64,
+    clause: &[Lit],
+    hints: &[u64],
+) -> io::Result<()> {
+    write!(writer, "{} ", id)?;
+    for lit in clause {
+        if lit.is_negative() {
+            write!(writer, "-")?;
+        }
+        write!(writer, "{} ", lit.to_dimacs())?;
+    }
+    write!(writer, "0 ")?;
+    for hint in hints {
+        write!(writer, "{} ", hint)?;
+    }
+    writeln!(writer, "0")
+}
+/// Each literal is mapped to `2 * var + sign` and written as a variable length
+/// integer, 7 bits per byte with the high bit set on all but the last byte.
+fn write_binary_step<W: Write>(writer: &mut W, delete: bool, clause: &[Lit]) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(clause.len() * 2 + 2);
+    buf.push(if delete { b'd' } else { b'a' });
+    for lit in clause {
+        let mut code = 2 * lit.to_dimacs() as u64 + lit.is_negative() as u64;
+        while code > 0x7f {
+            buf.push((code & 0x7f) as u8 | 0x80);
+            code >>= 7;
+        }
+        buf.push(code as u8);
+    }
+    buf.push(0);
+    writer.write_all(&buf)
+}
+impl Solver {
+    /// Starts writing a proof of unsatisfiability to `writer`.
+    ///
+    /// Every learnt clause and every clause deleted from the learnt clause
+    /// database is logged, and the empty clause is added once the formula is
+    /// found unsatisfiable. The proof refers to all clauses added to the
+    /// solver, so it should be started before adding any clause and checked
+    /// against the same formula, e.g. with drat-trim or [`check::check_lrat`].
+    /// LRAT ids number the clauses from 1 in the order they were added,
+    /// tautologies included. Learnt clauses take the ids after them, so for
+    /// LRAT every clause must be added before the first solve: a clause
+    /// added later gets an id past the learnt ones, which no longer matches
+    /// its position in the formula. Writes are buffered, so there is no need
+    /// to wrap `writer` in a [`BufWriter`].
+    ///
+    /// # Panics
+    ///
+    /// If an LRAT proof is started after adding clauses, see
+    /// [`Self::try_write_proof`].
+    pub fn write_proof<W: Write + Send + 'static>(&mut self, writer: W, format: ProofFormat) {
+        self.try_write_proof(writer, format)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Starts writing a proof like [`Self::write_proof`], failing with
+    /// [`RsatError::LateLratProof`] if `format` is LRAT and clauses were
+    /// already added: their ids and the units they implied are not in the
+    /// proof, so it could not refer to them. DRAT proofs only list lemmas
+    /// and can be started at any time.
+    pub fn try_write_proof<W: Write + Send + 'static>(
+        &mut self,
+        writer: W,
+        format: ProofFormat,
+    ) -> Result<(), RsatError> {
+        if format == ProofFormat::Lrat && self.clause_db.last_id() != 0 {
+            return Err(RsatError::LateLratProof);
+        }
+        self.proof = Some(ProofWriter::new(Box::new(writer), format));
+        Ok(())
+    }
+    /// Stops writing the proof and flushes it, returning the first error
+    /// that happened while writing.
+    pub fn finish_proof(&mut self) -> Result<(), RsatError> {
+        match self.proof.take() {
+            Some(proof) => proof.finish().map_err(RsatError::ProofIo),
+            None => Ok(()),
+        }
+    }
+    #[inline]
+    pub(crate) fn proof_add_clause(&mut self, id: u64, clause: &[Lit], hints: &[u64]) {
+        if let Some(proof) = &mut self.proof {
+            proof.add_clause(id, clause, hints);
+        }
+    }
+    /// Remembers the unit clause behind a level 0 assignment without a reason
+    /// clause for LRAT proofs, which is always the unit clause added last.
+    /// Propagated level 0 literals get a unit lemma once it's needed, see
+    /// [`Self::proof_unit_id`].
+    pub(crate) fn proof_root_unit(&mut self, lit: Lit, reason: PropReason) {
+        if let (Some(proof), PropReason::Unit) = (&mut self.proof, reason) {
+            if proof.is_lrat() {
+                proof.set_unit_id(lit.index(), self.clause_db.last_id());
+            }
+        }
+    }
+    /// The id of a unit clause asserting `lit`, which must be true at level
+    /// 0. A propagated literal is first derived as a unit lemma from its
+    /// reason, after the literals of the reason.
+    ///
+    /// Lemmas are only derived while solving, so their ids never clash with
+    /// the ids of the input clauses.
+    pub(crate) fn proof_unit_id(&mut self, lit: Lit) -> u64 {
+        let Some(proof) = &mut self.proof else {
+            return 0;
+        };
+        let mut stack = vec![lit];
+        while let Some(&top) = stack.last() {
+            if proof.unit_id(top.index()) != 0 {
+                stack.pop();
+                continue;
+            }
+            let reason = self.prop_graph.get_node(&top).reason;
+            let reason_lits = reason.lits(&self.clause_db);
+            let len = stack.len();
+            stack.extend(
+                reason_lits
+                    .iter()
+                    .filter(|reason_lit| proof.unit_id(reason_lit.index()) == 0)
+                    .map(|reason_lit| !*reason_lit),
+            );
+            if stack.len() > len {
+                continue;
+            }
+            let reason_id = reason
+                .id(&self.clause_db)
+                .expect("LRAT proofs are started before adding clauses");
+            let hints: Vec<u64> = reason_lits
+                .iter()
+                .map(|reason_lit| proof.unit_id(reason_lit.index()))
+                .chain(once(reason_id))
+                .collect();
+            let id = self.clause_db.new_id();
+            proof.add_clause(id, &[top], &hints);
+            proof.set_unit_id(top.index(), id);
+            stack.pop();
+        }
+        proof.unit_id(lit.index())
+    }
+    /// The LRAT hints deriving the empty clause from clause `id`, which is
+    /// falsified at level 0.
+    pub(crate) fn proof_falsified_hints(&mut self, id: u64, clause: &[Lit]) -> Vec<u64> {
+        if !self.proof.as_ref().is_some_and(|proof| proof.is_lrat()) {
+            return Vec::new();
+        }
+        clause
+            .iter()
+            .map(|lit| self.proof_unit_id(!*lit))
+            .chain(once(id))
+            .collect()
+    }
+}
@@ -0,0 +1,560 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Read},
+    mem,
+    str::FromStr,
+};
+
+use super::ProofFormat;
+use crate::{assign::Assignment, cnf::CnfFormula, error::ProofError, lit::Lit};
+
+/// Checks a proof that `formula` is unsatisfiable in any of the formats the
+/// solver writes, see [`check_drat`] and [`check_lrat`].
+pub fn check_proof<R: Read>(
+    formula: &CnfFormula,
+    proof: R,
+    format: ProofFormat,
+) -> Result<(), ProofError> {
+    match format {
+        ProofFormat::Drat | ProofFormat::BinaryDrat => check_drat(formula, proof, format),
+        ProofFormat::Lrat => check_lrat(formula, proof),
+    }
+}
+
+/// Checks a DRAT proof that `formula` is unsatisfiable, `format` being one of
+/// the DRAT formats.
+///
+/// Every lemma has to be a reverse unit propagation (RUP) of the clauses
+/// before it, or a resolution asymmetric tautology (RAT) on its first
+/// literal, and the proof has to derive the empty clause. As in drat-trim,
+/// deleting a unit clause or the reason of a top level assignment is ignored.
+pub fn check_drat<R: Read>(
+    formula: &CnfFormula,
+    proof: R,
+    format: ProofFormat,
+) -> Result<(), ProofError> {
+    let mut checker = DratChecker::default();
+    for clause in formula.iter() {
+        checker.add_clause(clause);
+    }
+    let mut steps = StepReader::new(proof);
+    let binary = format == ProofFormat::BinaryDrat;
+    while let Some((delete, clause)) = steps.next_drat_step(binary)? {
+        if delete {
+            if !checker.delete_clause(&clause) {
+                return Err(ProofError::UnknownClause { step: steps.step });
+            }
+            continue;
+        }
+        checker.resize(&clause);
+        if !checker.is_rup(&clause) && !checker.is_rat(&clause) {
+            return Err(ProofError::NotImplied { step: steps.step });
+        }
+        checker.add_clause(&clause);
+    }
+    if checker.inconsistent {
+        Ok(())
+    } else {
+        Err(ProofError::MissingEmptyClause)
+    }
+}
+
+/// Checks an LRAT proof that `formula` is unsatisfiable.
+///
+/// The clauses of the formula are numbered from 1 in input order, tautologies
+/// dropped while building the formula included, see
+/// [`CnfFormula::iter_with_ids`]. Unit propagation
+/// over the hints of a lemma has to make its negation conflicting, where a
+/// negative hint starts the hints of a RAT candidate as in the LRAT format.
+pub fn check_lrat<R: Read>(formula: &CnfFormula, proof: R) -> Result<(), ProofError> {
+    let mut checker = LratChecker::default();
+    for (id, clause) in formula.iter_with_ids() {
+        checker.resize(clause);
+        checker.derived_empty |= clause.is_empty();
+        checker.clauses.insert(id, clause.to_vec());
+    }
+    let mut steps = StepReader::new(proof);
+    while let Some(step) = steps.next_lrat_step()? {
+        match step {
+            LratStep::Add { id, lits, hints } => checker.add_lemma(id, lits, &hints, steps.step)?,
+            LratStep::Delete(ids) => {
+                for id in ids {
+                    if checker.clauses.remove(&id).is_none() {
+                        return Err(ProofError::UnknownClause { step: steps.step });
+                    }
+                }
+            }
+        }
+    }
+    if checker.derived_empty {
+        Ok(())
+    } else {
+        Err(ProofError::MissingEmptyClause)
+    }
+}
+
+/// Forward DRAT checker, the clauses are watched as in the solver.
+///
+/// Top level assignments stay on the trail for the whole check, the
+/// assignments of a RUP check are undone after it.
+#[derive(Debug, Default)]
+struct DratChecker {
+    clauses: Vec<Vec<Lit>>,
+    active: Vec<bool>,
+    /// Indices of the active clauses by their sorted literals.
+    lookup: HashMap<Vec<Lit>, Vec<usize>>,
+    /// Clauses watching a literal, indexed by its code.
+    watches: Vec<Vec<usize>>,
+    assignment: Assignment,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+    head: usize,
+    inconsistent: bool,
+}
+impl DratChecker {
+    fn resize(&mut self, clause: &[Lit]) {
+        let var_count = clause.iter().map(|lit| lit.index() + 1).max().unwrap_or(0);
+        if var_count > self.reasons.len() {
+            self.assignment.resize(var_count);
+            self.reasons.resize(var_count, None);
+            self.watches.resize(2 * var_count, Vec::new());
+        }
+    }
+    fn add_clause(&mut self, clause: &[Lit]) {
+        self.resize(clause);
+        let mut key = clause.to_vec();
+        key.sort();
+        key.dedup();
+        let index = self.clauses.len();
+        self.lookup.entry(key.clone()).or_default().push(index);
+        self.active.push(true);
+        let mut clause = key;
+        clause.sort_by_key(|lit| self.assignment.is_false(lit));
+        self.clauses.push(clause);
+        if self.inconsistent {
+            return;
+        }
+        let clause = &self.clauses[index];
+        match clause.len() {
+            0 => self.inconsistent = true,
+            1 => {}
+            _ => {
+                self.watches[clause[0].code()].push(index);
+                self.watches[clause[1].code()].push(index);
+                if !self.assignment.is_false(&clause[1]) {
+                    return;
+                }
+            }
+        }
+        let Some(&first) = clause.first() else {
+            return;
+        };
+        match self.assignment.value(&first) {
+            Some(true) => {}
+            Some(false) => self.inconsistent = true,
+            None => {
+                self.assign(first, Some(index));
+                self.inconsistent = self.propagate();
+            }
+        }
+    }
+    /// Returns false if the clause does not exist.
+    fn delete_clause(&mut self, clause: &[Lit]) -> bool {
+        let mut key = clause.to_vec();
+        key.sort();
+        key.dedup();
+        let Some(index) = self.lookup.get_mut(&key).and_then(|indices| indices.pop()) else {
+            return false;
+        };
+        let is_reason = self.clauses[index]
+            .iter()
+            .any(|lit| self.assignment.is_true(lit) && self.reasons[lit.index()] == Some(index));
+        if key.len() == 1 || is_reason {
+            self.lookup.get_mut(&key).unwrap().push(index);
+        } else {
+            self.active[index] = false;
+        }
+        true
+    }
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        self.assignment.assign(&lit);
+        self.reasons[lit.index()] = reason;
+        self.trail.push(lit);
+    }
+    fn backtrack(&mut self, trail_len: usize) {
+        for lit in self.trail.drain(trail_len..) {
+            self.assignment.unassign(&lit);
+        }
+        self.head = trail_len;
+    }
+    /// Returns whether unit propagation ran into a conflict.
+    fn propagate(&mut self) -> bool {
+        while let Some(&lit) = self.trail.get(self.head) {
+            self.head += 1;
+            let false_lit = !lit;
+            let mut watch_list = mem::take(&mut self.watches[false_lit.code()]);
+            let mut conflict = false;
+            let mut kept = 0;
+            let mut i = 0;
+            while i < watch_list.len() {
+                let index = watch_list[i];
+                i += 1;
+                if !self.active[index] {
+                    continue;
+                }
+                let clause = &mut self.clauses[index];
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                if !self.assignment.is_true(&clause[0]) {
+                    let replacement =
+                        (2..clause.len()).find(|&k| !self.assignment.is_false(&clause[k]));
+                    if let Some(k) = replacement {
+                        clause.swap(1, k);
+                        self.watches[clause[1].code()].push(index);
+                        continue;
+                    }
+                }
+                watch_list[kept] = index;
+                kept += 1;
+                let first = clause[0];
+                match self.assignment.value(&first) {
+                    Some(true) => {}
+                    Some(false) => {
+                        conflict = true;
+                        break;
+                    }
+                    None => self.assign(first, Some(index)),
+                }
+            }
+            watch_list.copy_within(i.., kept);
+            watch_list.truncate(kept + watch_list.len() - i);
+            self.watches[false_lit.code()] = watch_list;
+            if conflict {
+                return true;
+            }
+        }
+        false
+    }
+    fn is_rup(&mut self, clause: &[Lit]) -> bool {
+        if self.inconsistent {
+            return true;
+        }
+        let trail_len = self.trail.len();
+        let mut conflict = false;
+        for &lit in clause {
+            match self.assignment.value(&lit) {
+                Some(true) => {
+                    conflict = true;
+                    break;
+                }
+                Some(false) => {}
+                None => self.assign(!lit, None),
+            }
+        }
+        conflict = conflict || self.propagate();
+        self.backtrack(trail_len);
+        conflict
+    }
+    /// Checks that every resolvent on the first literal of `clause` is RUP.
+    fn is_rat(&mut self, clause: &[Lit]) -> bool {
+        let Some(&pivot) = clause.first() else {
+            return false;
+        };
+        for index in 0..self.clauses.len() {
+            if !self.active[index] || !self.clauses[index].contains(&!pivot) {
+                continue;
+            }
+            let mut resolvent = clause.to_vec();
+            resolvent.extend(self.clauses[index].iter().filter(|&&lit| lit != !pivot));
+            if !self.is_rup(&resolvent) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, Default)]
+struct LratChecker {
+    clauses: HashMap<u64, Vec<Lit>>,
+    assignment: Assignment,
+    trail: Vec<Lit>,
+    derived_empty: bool,
+}
+impl LratChecker {
+    fn resize(&mut self, clause: &[Lit]) {
+        let var_count = clause.iter().map(|lit| lit.index() + 1).max().unwrap_or(0);
+        if var_count > self.assignment.len() {
+            self.assignment.resize(var_count);
+        }
+    }
+    fn add_lemma(
+        &mut self,
+        id: u64,
+        lemma: Vec<Lit>,
+        hints: &[i64],
+        step: usize,
+    ) -> Result<(), ProofError> {
+        if self.clauses.contains_key(&id) {
+            return Err(ProofError::InvalidStep { step });
+        }
+        self.resize(&lemma);
+        let implied = self.is_implied(&lemma, hints, step);
+        for lit in self.trail.drain(..) {
+            self.assignment.unassign(&lit);
+        }
+        if !implied? {
+            return Err(ProofError::NotImplied { step });
+        }
+        self.derived_empty |= lemma.is_empty();
+        self.clauses.insert(id, lemma);
+        Ok(())
+    }
+    fn assign(&mut self, lit: Lit) {
+        self.assignment.assign(&lit);
+        self.trail.push(lit);
+    }
+    fn is_implied(
+        &mut self,
+        lemma: &[Lit],
+        hints: &[i64],
+        step: usize,
+    ) -> Result<bool, ProofError> {
+        for &lit in lemma {
+            match self.assignment.value(&lit) {
+                Some(true) => return Ok(true),
+                Some(false) => {}
+                None => self.assign(!lit),
+            }
+        }
+        let rup_len = hints
+            .iter()
+            .position(|&hint| hint < 0)
+            .unwrap_or(hints.len());
+        if self.propagate_hints(&hints[..rup_len], step)? {
+            return Ok(true);
+        }
+        let Some(&pivot) = lemma.first() else {
+            return Ok(false);
+        };
+        let mut groups = HashMap::new();
+        let mut rest = &hints[rup_len..];
+        while let Some((&candidate, tail)) = rest.split_first() {
+            let len = tail.iter().position(|&hint| hint < 0).unwrap_or(tail.len());
+            groups.insert(candidate.unsigned_abs(), &tail[..len]);
+            rest = &tail[len..];
+        }
+        let mut candidates: Vec<u64> = self
+            .clauses
+            .iter()
+            .filter(|(_, clause)| clause.contains(&!pivot))
+            .map(|(id, _)| *id)
+            .collect();
+        candidates.sort_unstable();
+        let trail_len = self.trail.len();
+        for id in candidates {
+            let Some(group) = groups.get(&id) else {
+                return Ok(false);
+            };
+            let mut satisfied = false;
+            for lit in self.clauses[&id].clone() {
+                match self.assignment.value(&lit) {
+                    _ if lit == !pivot => {}
+                    Some(true) => satisfied = true,
+                    Some(false) => {}
+                    None => self.assign(!lit),
+                }
+            }
+            let conflict = satisfied || self.propagate_hints(group, step)?;
+            for lit in self.trail.drain(trail_len..) {
+                self.assignment.unassign(&lit);
+            }
+            if !conflict {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+    /// Propagates the hinted clauses in order, each has to be unit or
+    /// conflicting. Returns whether a conflict was reached.
+    fn propagate_hints(&mut self, hints: &[i64], step: usize) -> Result<bool, ProofError> {
+        for &hint in hints {
+            let clause = self
+                .clauses
+                .get(&hint.unsigned_abs())
+                .ok_or(ProofError::UnknownClause { step })?;
+            let mut unassigned = None;
+            for &lit in clause {
+                match self.assignment.value(&lit) {
+                    Some(false) => {}
+                    None if unassigned.is_none() || unassigned == Some(lit) => {
+                        unassigned = Some(lit)
+                    }
+                    _ => return Ok(false),
+                }
+            }
+            match unassigned {
+                Some(lit) => self.assign(lit),
+                None => return Ok(true),
+            }
+        }
+        Ok(false)
+    }
+}
+
+enum LratStep {
+    Add {
+        id: u64,
+        lits: Vec<Lit>,
+        hints: Vec<i64>,
+    },
+    Delete(Vec<u64>),
+}
+
+struct StepReader<R> {
+    reader: BufReader<R>,
+    line: String,
+    /// Tokens of the current line in reverse order.
+    tokens: Vec<String>,
+    /// Number of the step being read, starting from 1.
+    step: usize,
+}
+impl<R: Read> StepReader<R> {
+    fn new(reader: R) -> Self {
+        StepReader {
+            reader: BufReader::new(reader),
+            line: String::new(),
+            tokens: Vec::new(),
+            step: 0,
+        }
+    }
+    fn invalid(&self) -> ProofError {
+        ProofError::InvalidStep { step: self.step }
+    }
+    /// Returns the next token, skipping comment lines.
+    fn token(&mut self) -> io::Result<Option<String>> {
+        while self.tokens.is_empty() {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+            if self.line.trim_start().starts_with('c') {
+                continue;
+            }
+            self.tokens = self
+                .line
+                .split_whitespace()
+                .rev()
+                .map(String::from)
+                .collect();
+        }
+        Ok(self.tokens.pop())
+    }
+    fn number<T: FromStr>(&mut self) -> Result<T, ProofError> {
+        let token = self.token()?.ok_or_else(|| self.invalid())?;
+        token.parse().map_err(|_| self.invalid())
+    }
+    /// Reads numbers up to the terminating `0`.
+    fn numbers(&mut self) -> Result<Vec<i64>, ProofError> {
+        let mut numbers = vec![];
+        loop {
+            match self.number()? {
+                0 => return Ok(numbers),
+                number => numbers.push(number),
+            }
+        }
+    }
+    fn lits(&mut self) -> Result<Vec<Lit>, ProofError> {
+        let numbers = self.numbers()?;
+        numbers
+            .into_iter()
+            .map(|number| self.lit(number.unsigned_abs(), number > 0))
+            .collect()
+    }
+    /// The literal of a variable read from the proof, which may be out of
+    /// range for this build.
+    fn lit(&self, var: u64, positive: bool) -> Result<Lit, ProofError> {
+        usize::try_from(var)
+            .ok()
+            .and_then(|var| Lit::try_from_dimacs(var, positive).ok())
+            .ok_or_else(|| self.invalid())
+    }
+    fn byte(&mut self) -> io::Result<Option<u8>> {
+        let Some(&byte) = self.reader.fill_buf()?.first() else {
+            return Ok(None);
+        };
+        self.reader.consume(1);
+        Ok(Some(byte))
+    }
+    /// Reads the next clause addition or deletion, returning whether it is a
+    /// deletion together with the clause.
+    fn next_drat_step(&mut self, binary: bool) -> Result<Option<(bool, Vec<Lit>)>, ProofError> {
+        self.step += 1;
+        if binary {
+            return self.next_binary_step();
+        }
+        let Some(token) = self.token()? else {
+            return Ok(None);
+        };
+        let delete = token == "d";
+        if !delete {
+            self.tokens.push(token);
+        }
+        Ok(Some((delete, self.lits()?)))
+    }
+    fn next_binary_step(&mut self) -> Result<Option<(bool, Vec<Lit>)>, ProofError> {
+        let delete = match self.byte()? {
+            None => return Ok(None),
+            Some(b'a') => false,
+            Some(b'd') => true,
+            Some(_) => return Err(self.invalid()),
+        };
+        let mut clause = vec![];
+        loop {
+            let mut code = 0u64;
+            let mut shift = 0;
+            loop {
+                let byte = self.byte()?.ok_or_else(|| self.invalid())?;
+                if shift > 56 {
+                    return Err(self.invalid());
+                }
+                code |= u64::from(byte & 0x7f) << shift;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                shift += 7;
+            }
+            match code {
+                0 => return Ok(Some((delete, clause))),
+                1 => return Err(self.invalid()),
+                _ => clause.push(self.lit(code >> 1, code & 1 == 0)?),
+            }
+        }
+    }
+    fn next_lrat_step(&mut self) -> Result<Option<LratStep>, ProofError> {
+        self.step += 1;
+        let Some(token) = self.token()? else {
+            return Ok(None);
+        };
+        let id: u64 = token.parse().map_err(|_| self.invalid())?;
+        match self.token()? {
+            Some(token) if token == "d" => {
+                let ids = self.numbers()?;
+                if ids.iter().any(|&id| id < 0) {
+                    return Err(self.invalid());
+                }
+                Ok(Some(LratStep::Delete(
+                    ids.into_iter().map(|id| id as u64).collect(),
+                )))
+            }
+            Some(token) => {
+                self.tokens.push(token);
+                let lits = self.lits()?;
+                let hints = self.numbers()?;
+                Ok(Some(LratStep::Add { id, lits, hints }))
+            }
+            None => Err(self.invalid()),
+        }
+    }
+}
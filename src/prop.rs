@@ -7,7 +7,6 @@ use crate::{
     solver::{ClauseRef, Solver},
     watch::Watch,
 };
-use anyhow::Result;
 #[derive(Debug, Default)]
 pub(crate) struct PropQueue {
     trail: Vec<Lit>,
@@ -32,6 +31,10 @@ impl PropQueue {
     pub(crate) fn current_level(&self) -> usize {
         self.level_with_trail_len.len()
     }
+    /// The literals assigned before decision level `level` was opened.
+    pub(crate) fn trail_before_level(&self, level: usize) -> &[Lit] {
+        &self.trail[..self.level_with_trail_len[level - 1]]
+    }
 }
 impl From<&[Lit]> for PropQueue {
     fn from(value: &[Lit]) -> Self {
@@ -52,6 +55,7 @@ impl Deref for PropQueue {
 impl Solver {
     pub(crate) fn propagate(&mut self) -> Result<(), Conflict> {
         while let Some(lit) = self.prop_queue.pop_queue() {
+            self.stats.propagations += 1;
             let mut watch_list = self.watch_lists.pop_watch_list(lit);
             let mut i = 0;
             'watch: loop {
@@ -61,12 +65,14 @@ impl Solver {
                 let watch = watch_list.get_mut(i).unwrap();
                 i += 1;
                 match watch.cref {
-                    ClauseRef::Binary(_) => match self.assignment.value(&watch.blocking) {
+                    ClauseRef::Binary(index) => match self.assignment.value(&watch.blocking) {
                         Some(false) => {
-                            return Err(Conflict::Binary([!lit, watch.blocking]));
+                            let conflict = Conflict::Binary([!lit, watch.blocking], index);
+                            self.watch_lists.set_watch_list(lit, watch_list);
+                            return Err(conflict);
                         }
                         None => {
-                            self.add_assign(&watch.blocking, PropReason::Binary([!lit]));
+                            self.add_assign(&watch.blocking, PropReason::Binary([!lit], index));
                         }
                         Some(true) => {}
                     },
@@ -94,7 +100,9 @@ impl Solver {
                             }
                         }
                         if self.assignment.is_false(&clause[0]) {
-                            return Err(Conflict::Long(clause.to_vec()));
+                            let conflict = Conflict::Long(index);
+                            self.watch_lists.set_watch_list(lit, watch_list);
+                            return Err(conflict);
                         }
                         let first = clause[0];
                         self.add_assign(&first, PropReason::Long(watch.cref));
@@ -114,8 +122,14 @@ impl Solver {
         self.prop_queue.push_back(lit);
         self.prop_graph
             .update_node(lit, prop_reason, self.prop_queue.current_level());
+        if self.proof.is_some() && self.prop_queue.current_level() == 0 {
+            self.proof_root_unit(*lit, prop_reason);
+        }
     }
     pub(crate) fn backtrack(&mut self, backtrack_level: usize) {
+        if backtrack_level >= self.prop_queue.current_level() {
+            return;
+        }
         let new_len = self.prop_queue.level_with_trail_len[backtrack_level];
         self.prop_queue
             .level_with_trail_len
@@ -123,6 +137,8 @@ impl Solver {
         self.prop_queue.pos = new_len;
         for lit in self.prop_queue.trail[new_len..].iter() {
             self.assignment.unassign(lit);
+            self.vsids.push(lit.index());
+            self.phases.save(lit);
         }
         self.prop_queue.trail.truncate(new_len);
     }
@@ -0,0 +1,153 @@
+use crate::config::RestartPolicy;
+
+/// Exponential moving average, bias corrected for the first samples like
+/// the ones in CaDiCaL.
+#[derive(Debug, Clone, Copy)]
+struct Ema {
+    value: f64,
+    alpha: f64,
+    beta: f64,
+    /// Weight of the samples seen so far, approaches 1.
+    weight: f64,
+}
+impl Ema {
+    fn new(alpha: f64) -> Self {
+        Ema {
+            value: 0.0,
+            alpha,
+            beta: 1.0 - alpha,
+            weight: 0.0,
+        }
+    }
+    fn update(&mut self, sample: f64) {
+        self.weight = self.weight * self.beta + self.alpha;
+        self.value += self.alpha * (sample - self.value);
+    }
+    fn get(&self) -> f64 {
+        if self.weight == 0.0 {
+            0.0
+        } else {
+            self.value / self.weight
+        }
+    }
+}
+
+/// Decides when the solver restarts, following the configured policy.
+#[derive(Debug)]
+pub(crate) struct Restarts {
+    policy: RestartPolicy,
+    /// Conflicts since the last restart.
+    conflicts: u64,
+    /// Conflicts allowed until the next restart, unused by glucose restarts.
+    limit: u64,
+    restarts: u64,
+    fast_lbd: Ema,
+    slow_lbd: Ema,
+}
+impl Restarts {
+    pub(crate) fn new(policy: RestartPolicy) -> Self {
+        let mut restarts = Restarts {
+            policy,
+            conflicts: 0,
+            limit: 0,
+            restarts: 0,
+            fast_lbd: Ema::new(0.03),
+            slow_lbd: Ema::new(1e-5),
+        };
+        restarts.limit = restarts.next_limit();
+        restarts
+    }
+    /// Called for every learnt clause.
+    #[inline]
+    pub(crate) fn on_conflict(&mut self, lbd: usize) {
+        self.conflicts += 1;
+        if let RestartPolicy::Glucose { .. } = self.policy {
+            self.fast_lbd.update(lbd as f64);
+            self.slow_lbd.update(lbd as f64);
+        }
+    }
+    #[inline]
+    pub(crate) fn should_restart(&self) -> bool {
+        match self.policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::Glucose {
+                margin,
+                min_conflicts,
+            } => {
+                self.conflicts >= min_conflicts
+                    && self.fast_lbd.get() > margin * self.slow_lbd.get()
+            }
+            _ => self.conflicts >= self.limit,
+        }
+    }
+    pub(crate) fn on_restart(&mut self) {
+        self.restarts += 1;
+        self.conflicts = 0;
+        self.limit = self.next_limit();
+    }
+    fn next_limit(&self) -> u64 {
+        match self.policy {
+            RestartPolicy::Never | RestartPolicy::Glucose { .. } => u64::MAX,
+            RestartPolicy::Fixed { interval } => interval,
+            RestartPolicy::Geometric { initial, factor } => {
+                (initial as f64 * factor.powi(self.restarts as i32)) as u64
+            }
+            RestartPolicy::Luby { unit } => unit * luby(self.restarts + 1),
+        }
+    }
+}
+
+/// The `i`-th element (starting at 1) of the Luby sequence
+/// 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, ...
+fn luby(i: u64) -> u64 {
+    let mut i = i;
+    loop {
+        // find the smallest 2^k - 1 >= i
+        let mut k = 1;
+        while (1u64 << k) - 1 < i {
+            k += 1;
+        }
+        if (1u64 << k) - 1 == i {
+            return 1 << (k - 1);
+        }
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luby() {
+        let sequence: Vec<u64> = (1..=15).map(luby).collect();
+        assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+    #[test]
+    fn test_geometric() {
+        let mut restarts = Restarts::new(RestartPolicy::Geometric {
+            initial: 100,
+            factor: 1.5,
+        });
+        let mut limits = vec![];
+        for _ in 0..4 {
+            limits.push(restarts.limit);
+            restarts.on_restart();
+        }
+        assert_eq!(limits, vec![100, 150, 225, 337]);
+    }
+    #[test]
+    fn test_glucose() {
+        let mut restarts = Restarts::new(RestartPolicy::Glucose {
+            margin: 1.25,
+            min_conflicts: 2,
+        });
+        for _ in 0..100 {
+            restarts.on_conflict(4);
+        }
+        assert!(!restarts.should_restart());
+        for _ in 0..20 {
+            restarts.on_conflict(12);
+        }
+        assert!(restarts.should_restart());
+    }
+}
@@ -1,10 +1,27 @@
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
 use crate::{
     assign::Assignment,
     cdcl::AnalyzeConflict,
+    config::{PolarityMode, SolverConfig},
     db::ClauseDb,
+    error::{RsatError, UnsatisfiedClause},
     graph::{PropGraph, PropReason},
-    lit::Lit,
+    limit::Budget,
+    lit::{Lit, Var},
+    phase::Phases,
+    proof::ProofWriter,
     prop::PropQueue,
+    restart::Restarts,
+    stats::{SampledTimer, SolverStats},
+    vsids::Vsids,
     watch::Watchlists,
 };
 
@@ -15,122 +32,519 @@ pub(super) enum ClauseRef {
     Binary(usize),
     Long(usize),
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum SolveState {
+    #[default]
     Unknown,
     Sat,
     Unsat,
 }
-impl Default for SolveState {
-    fn default() -> Self {
-        Self::Unknown
+/// Cancels a running solve from another thread, see
+/// [`Solver::interrupt_handle`].
+#[derive(Debug, Clone, Default)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+impl InterruptHandle {
+    /// Makes the current, or else the next, call to solve return
+    /// [`SolveState::Unknown`] soon.
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::Relaxed);
     }
 }
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Solver {
     pub(crate) assignment: Assignment,
+    /// The literals of the clause being added, to find duplicates.
+    clause_marks: Assignment,
     pub(crate) clause_db: ClauseDb,
-    max_lit_index: usize,
-    pub(crate) default_polarity: bool,
     pub(crate) watch_lists: Watchlists,
     pub(crate) prop_graph: PropGraph,
     pub(crate) prop_queue: PropQueue,
     pub(crate) analyze_conflict: AnalyzeConflict,
+    pub(crate) vsids: Vsids,
+    pub(crate) phases: Phases,
+    pub(crate) restarts: Restarts,
+    pub(crate) budget: Budget,
+    pub(crate) stats: SolverStats,
+    propagate_timer: SampledTimer,
+    analyze_timer: SampledTimer,
+    interrupt: InterruptHandle,
     pub(crate) state: SolveState,
+    root_unsat: bool,
+    /// Id and literals of a clause that was added falsified at level 0, the
+    /// empty clause is derived from it once solving starts.
+    root_conflict: Option<(u64, Vec<Lit>)>,
+    /// Copy of the added clauses when models are verified.
+    original: Option<CnfFormula>,
+    /// The clause the last model falsified, if it was verified.
+    verify_error: Option<UnsatisfiedClause>,
+    /// Variables in use or declared by an added formula, the per variable
+    /// vectors only grow to the ones in use.
+    var_count: usize,
+    /// Values of the variables in use when the model was found, declared but
+    /// unused variables after them are false.
+    model: Vec<Lit>,
+    model_var_count: usize,
+    pub(crate) failed_assumptions: Vec<Lit>,
+    pub(crate) proof: Option<ProofWriter>,
+}
+impl Default for Solver {
+    fn default() -> Self {
+        Solver::with_config(SolverConfig::default())
+    }
 }
 
 impl Solver {
+    /// Adds every clause of `formula`. Proof ids follow
+    /// [`CnfFormula::iter_with_ids`], so the tautologies the formula dropped
+    /// keep their ids as in the DIMACS input.
+    ///
+    /// Variables declared by the formula's header count towards
+    /// [`Self::var_count`] and the model, but memory is only allocated for
+    /// the ones its clauses use.
     pub fn add_formula(mut self, formula: &CnfFormula) -> Self {
-        self.max_lit_index = self.max_lit_index.max(formula.get_max_lit_index());
-        let var_count = self.max_lit_index + 1;
-        self.assignment.resize(var_count);
-        self.watch_lists.resize(var_count);
-        self.prop_graph.resize(var_count);
-        self.analyze_conflict.resize(var_count);
-        self.clause_db.add_formula(formula);
-        
+        self.var_count = self.var_count.max(formula.var_count());
+        let first_id = self.clause_db.last_id();
+        for (id, clause) in formula.iter_with_ids() {
+            self.clause_db.skip_ids(first_id + id - 1);
+            if let Some(original) = &mut self.original {
+                original.add_clause(clause);
+            }
+            // the formula already removed duplicates and tautologies
+            self.add_clean_clause(clause.to_vec());
+        }
+        self.clause_db
+            .skip_ids(first_id + formula.input_clause_count());
         self
     }
+    /// Adds a clause, also between calls to [`Self::solve_with_assumptions`].
+    ///
+    /// Duplicate literals are removed and tautologies are dropped.
+    pub fn add_clause(&mut self, clause: &[Lit]) {
+        if let Some(original) = &mut self.original {
+            original.add_clause(clause);
+        }
+        match self.clean_clause(clause) {
+            Some(lits) => self.add_clean_clause(lits),
+            None => {
+                // keep the ids of the following clauses in line with the input
+                self.clause_db.new_id();
+            }
+        }
+    }
+    /// Removes duplicate literals, `None` for tautologies.
+    fn clean_clause(&mut self, clause: &[Lit]) -> Option<Vec<Lit>> {
+        if let Some(max) = clause.iter().map(|lit| lit.index()).max() {
+            if max >= self.clause_marks.len() {
+                self.clause_marks.resize(max + 1);
+            }
+        }
+        let mut lits: Vec<Lit> = Vec::with_capacity(clause.len());
+        let mut tautology = false;
+        for lit in clause {
+            match self.clause_marks.value(lit) {
+                Some(true) => {}
+                Some(false) => {
+                    tautology = true;
+                    break;
+                }
+                None => {
+                    self.clause_marks.assign(lit);
+                    lits.push(*lit);
+                }
+            }
+        }
+        for lit in &lits {
+            self.clause_marks.unassign(lit);
+        }
+        (!tautology).then_some(lits)
+    }
+    /// Adds a clause without duplicate literals that isn't a tautology.
+    fn add_clean_clause(&mut self, mut lits: Vec<Lit>) {
+        self.backtrack(0);
+        if let Some(max) = lits.iter().map(|lit| lit.index()).max() {
+            self.resize(max + 1);
+        }
+        // literals false at level 0 stay false, so only watch them when
+        // there is nothing else left to watch
+        lits.sort_by_key(|lit| self.assignment.is_false(lit));
+        let clause_ref = self.clause_db.add_clause(&lits);
+        if lits.first().is_none_or(|lit| self.assignment.is_false(lit)) {
+            if self.root_conflict.is_none() {
+                self.root_conflict = Some((self.clause_db.last_id(), lits));
+            }
+            return;
+        }
+        match clause_ref {
+            None => {
+                if !self.assignment.is_true(&lits[0]) {
+                    self.add_assign(&lits[0], PropReason::Unit);
+                }
+            }
+            Some(clause_ref) => {
+                self.watch_lists.watch_clause(clause_ref, lits[0], lits[1]);
+                if self.assignment.is_false(&lits[1]) && !self.assignment.is_true(&lits[0]) {
+                    self.add_assign(&lits[0], Self::reason_of(clause_ref, &lits));
+                }
+            }
+        }
+    }
+    /// A solver always deciding variables with the given polarity.
     pub fn new(decision_default_polarity: bool) -> Self {
-        let mut solve = Solver::default();
-        solve.default_polarity = decision_default_polarity;
-        solve
+        Solver::with_config(SolverConfig {
+            polarity: if decision_default_polarity {
+                PolarityMode::True
+            } else {
+                PolarityMode::False
+            },
+            ..Default::default()
+        })
+    }
+    /// # Panics
+    ///
+    /// If the config is invalid, see [`Self::try_with_config`].
+    pub fn with_config(config: SolverConfig) -> Self {
+        Self::try_with_config(config).unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Creates a solver, failing with [`RsatError::InvalidConfig`] if
+    /// [`SolverConfig::validate`] rejects the config.
+    pub fn try_with_config(config: SolverConfig) -> Result<Self, RsatError> {
+        config.validate()?;
+        Ok(Solver {
+            assignment: Assignment::default(),
+            clause_marks: Assignment::default(),
+            clause_db: ClauseDb::new(config.reduce_interval),
+            watch_lists: Watchlists::default(),
+            prop_graph: PropGraph::default(),
+            prop_queue: PropQueue::default(),
+            analyze_conflict: AnalyzeConflict::new(config.minimize),
+            vsids: Vsids::default(),
+            phases: Phases::new(config.polarity, config.random_seed),
+            restarts: Restarts::new(config.restart),
+            budget: Budget::new(config.limits),
+            stats: SolverStats::default(),
+            propagate_timer: SampledTimer::default(),
+            analyze_timer: SampledTimer::default(),
+            interrupt: InterruptHandle::default(),
+            state: SolveState::default(),
+            root_unsat: false,
+            root_conflict: None,
+            original: config.verify_model.then(CnfFormula::new),
+            verify_error: None,
+            var_count: 0,
+            model: Vec::new(),
+            model_var_count: 0,
+            failed_assumptions: Vec::new(),
+            proof: None,
+        })
     }
     pub fn solve(mut self) -> Self {
-        self.generate_watch();
-        for lit in self.clause_db.assign_clauses.clone() {
-            self.add_assign(&lit, PropReason::Unit);
+        self.solve_with_assumptions(&[]);
+        self
+    }
+    /// Solves the formula with every literal in `assumptions` forced to be
+    /// true for this call only.
+    ///
+    /// Returns [`SolveState::Unknown`] if one of the configured
+    /// [`Limits`](crate::config::Limits) runs out first. Each call gets the
+    /// full budget again and keeps what was learnt before.
+    ///
+    /// Learnt clauses are kept between calls and more clauses may be added
+    /// in between, so closely related queries get cheaper over time. The
+    /// solver is back at decision level 0 when this returns.
+    pub fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> SolveState {
+        self.backtrack(0);
+        self.model.clear();
+        self.model_var_count = 0;
+        self.verify_error = None;
+        self.failed_assumptions.clear();
+        if let Some(max) = assumptions.iter().map(|lit| lit.index()).max() {
+            self.resize(max + 1);
+        }
+        let start = Instant::now();
+        self.budget.start(&self.stats);
+        self.state = self.search(assumptions);
+        if self.state == SolveState::Sat {
+            self.model = self
+                .assignment
+                .iter()
+                .enumerate()
+                .map(|(index, value)| Lit::from_index(index, value.unwrap_or(false)))
+                .collect();
+            self.model_var_count = self.var_count;
+            if let Some(original) = &self.original {
+                self.verify_error = original.evaluate(&self.model).err();
+            }
+        }
+        self.backtrack(0);
+        self.stats.solve_time += start.elapsed();
+        info!(
+            "solved as {:?} after {} conflicts",
+            self.state, self.stats.conflicts
+        );
+        self.state
+    }
+    /// Like [`Self::solve_with_assumptions`], but returns whether the formula
+    /// is satisfiable and [`RsatError::BudgetExhausted`] instead of
+    /// [`SolveState::Unknown`], or [`RsatError::InvalidModel`] if the model
+    /// failed verification.
+    pub fn try_solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Result<bool, RsatError> {
+        match self.solve_with_assumptions(assumptions) {
+            SolveState::Sat => match &self.verify_error {
+                Some(clause) => Err(RsatError::InvalidModel(clause.clone())),
+                None => Ok(true),
+            },
+            SolveState::Unsat => Ok(false),
+            SolveState::Unknown => Err(RsatError::BudgetExhausted),
+        }
+    }
+    fn search(&mut self, assumptions: &[Lit]) -> SolveState {
+        if let Some((id, clause)) = self.root_conflict.take() {
+            let hints = self.proof_falsified_hints(id, &clause);
+            self.set_root_unsat(&hints);
+        }
+        if self.root_unsat {
+            return SolveState::Unsat;
         }
         loop {
-            match self.propagate() {
-                Ok(_) => {}
-                Err(conflict) => {
-                    let backtrack_level = self.analyze_conflict(conflict).unwrap();
-                    self.backtrack(backtrack_level);
-                    let learnt_clause = self.analyze_conflict.get_learnt_clause();
-                    match learnt_clause {
-                        Some(ref clause) => {
-                            let reason = if let Some(r) = self.clause_db.add_clause(clause) {
-                                self.watch_lists.watch_clause(r, clause[0], clause[1]);
-                                match r {
-                                    ClauseRef::Binary(_) => PropReason::Binary([clause[1]]),
-                                    ClauseRef::Long(_) => PropReason::Long(r),
-                                }
-                            } else {
-                                PropReason::Unit
-                            };
-                            self.add_assign(&clause[0], reason);
-                        }
-                        None => {
-                            self.state = SolveState::Unsat;
-                            break;
-                        }
+            if self.budget.exhausted(&self.stats) {
+                return SolveState::Unknown;
+            }
+            if self.interrupt.0.load(Ordering::Relaxed) {
+                // cleared so the solver can be used again
+                self.interrupt.0.store(false, Ordering::Relaxed);
+                return SolveState::Unknown;
+            }
+            let start = self.propagate_timer.start();
+            let propagated = self.propagate();
+            SampledTimer::stop(start, &mut self.stats.propagate_time);
+            if let Err(conflict) = propagated {
+                self.stats.conflicts += 1;
+                if self.prop_queue.current_level() == 0 {
+                    let (id, clause) = conflict.clause(&self.clause_db);
+                    let clause = clause.to_vec();
+                    let hints = self.proof_falsified_hints(id, &clause);
+                    self.set_root_unsat(&hints);
+                    return SolveState::Unsat;
+                }
+                let level = self.prop_queue.current_level();
+                trace!("conflict at level {}", level);
+                self.phases
+                    .on_conflict(self.prop_queue.trail_before_level(level));
+                let start = self.analyze_timer.start();
+                let backtrack_level = self.analyze_conflict(conflict);
+                SampledTimer::stop(start, &mut self.stats.analyze_time);
+                trace!("backtrack from level {} to {}", level, backtrack_level);
+                self.backtrack(backtrack_level);
+                let hints = self.analyze_conflict.take_hints();
+                match self.analyze_conflict.get_learnt_clause() {
+                    Some(clause) => self.learn_clause(&clause, self.analyze_conflict.lbd(), &hints),
+                    None => {
+                        self.set_root_unsat(&hints);
+                        return SolveState::Unsat;
                     }
-                    continue;
                 }
-            };
+                if self.clause_db.on_conflict() {
+                    let start = Instant::now();
+                    self.reduce_db();
+                    self.stats.reduce_time += start.elapsed();
+                }
+                self.restarts.on_conflict(self.analyze_conflict.lbd());
+                if self.restarts.should_restart() {
+                    self.restarts.on_restart();
+                    self.stats.restarts += 1;
+                    debug!("restart after {} conflicts", self.stats.conflicts);
+                    self.backtrack(0);
+                }
+                continue;
+            }
+            let level = self.prop_queue.current_level();
+            if let Some(assumption) = assumptions.get(level) {
+                match self.assignment.value(assumption) {
+                    Some(false) => {
+                        self.analyze_final(*assumption);
+                        return SolveState::Unsat;
+                    }
+                    // keep one decision level per assumption, even if it's
+                    // already implied by the earlier ones
+                    Some(true) => self.prop_queue.new_decision_level(),
+                    None => {
+                        self.prop_queue.new_decision_level();
+                        self.add_assign(assumption, PropReason::Unit);
+                    }
+                }
+                continue;
+            }
             if !self.make_decision() {
-                break;
-            };
+                return SolveState::Sat;
+            }
+            self.stats.decisions += 1;
         }
-        self.check_satisfied();
-        self
     }
-    pub fn check_satisfied(&mut self) -> bool {
-        for bin in &self.clause_db.binary_clauses {
-            if !self.assignment.is_true(&bin[0]) && !self.assignment.is_true(&bin[1]) {
-                // dbg!(bin);
-                self.state = SolveState::Unsat;
-                return false;
+    /// Adds the learnt clause and asserts its first literal, the solver must
+    /// already be backtracked to the clause's assertion level.
+    fn learn_clause(&mut self, clause: &[Lit], lbd: usize, hints: &[u64]) {
+        debug!("learn {:?} with lbd {}", clause, lbd);
+        let clause_ref = self.clause_db.add_learnt_clause(clause, lbd);
+        self.stats.learnt_clauses += 1;
+        self.stats.learnt_literals += clause.len() as u64;
+        self.proof_add_clause(self.clause_db.last_id(), clause, hints);
+        let reason = match clause_ref {
+            Some(clause_ref) => {
+                self.watch_lists
+                    .watch_clause(clause_ref, clause[0], clause[1]);
+                Self::reason_of(clause_ref, clause)
             }
+            None => PropReason::Unit,
+        };
+        self.add_assign(&clause[0], reason);
+    }
+    /// Marks the formula as unsatisfiable without any assumptions, `hints`
+    /// derive the empty clause in an LRAT proof.
+    fn set_root_unsat(&mut self, hints: &[u64]) {
+        if !self.root_unsat {
+            self.root_unsat = true;
+            let id = self.clause_db.new_id();
+            self.proof_add_clause(id, &[], hints);
         }
-        for clause in &self.clause_db.long_clauses {
-            let mut flag = false;
-            for lit in clause {
-                if self.assignment.is_true(lit) {
-                    flag = true;
-                    break;
-                }
-            }
-            if !flag {
-                // dbg!(clause);
-                self.state = SolveState::Unsat;
-                return false;
-            }
+    }
+    /// The reason for propagating `clause[0]`, with every other literal false.
+    #[inline]
+    fn reason_of(clause_ref: ClauseRef, clause: &[Lit]) -> PropReason {
+        match clause_ref {
+            ClauseRef::Binary(index) => PropReason::Binary([clause[1]], index),
+            ClauseRef::Long(_) => PropReason::Long(clause_ref),
         }
-        self.state = SolveState::Sat;
-        true
+    }
+    /// Number of variables, the highest one used in any clause, declared by
+    /// an added formula or allocated by [`Self::new_var`].
+    pub fn var_count(&self) -> usize {
+        self.var_count
+    }
+    /// Allocates a variable that isn't used by any clause yet, e.g. for
+    /// auxiliary variables of an encoding. It comes after every declared
+    /// variable, so those are allocated too.
+    ///
+    /// # Panics
+    ///
+    /// If the solver already has [`Lit::MAX_VAR`] variables.
+    pub fn new_var(&mut self) -> Var {
+        let var = Var::from_index(self.var_count());
+        self.resize(var.index() + 1);
+        var
+    }
+    /// The positive literal of a fresh variable, see [`Self::new_var`].
+    pub fn new_lit(&mut self) -> Lit {
+        self.new_var().lit(true)
+    }
+    /// Grows every per variable vector to hold `var_count` variables.
+    fn resize(&mut self, var_count: usize) {
+        self.var_count = self.var_count.max(var_count);
+        if var_count <= self.assignment.len() {
+            return;
+        }
+        self.assignment.resize(var_count);
+        self.watch_lists.resize(var_count);
+        self.prop_graph.resize(var_count);
+        self.analyze_conflict.resize(var_count);
+        self.vsids.resize(var_count);
+        self.phases.resize(var_count);
+    }
+    /// A handle to interrupt solving from another thread.
+    ///
+    /// An interrupted call returns [`SolveState::Unknown`] and leaves the
+    /// solver at decision level 0 with everything learnt so far, ready to
+    /// be used again.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+    pub fn stats(&self) -> &SolverStats {
+        &self.stats
+    }
+    pub fn state(&self) -> SolveState {
+        self.state
+    }
+    /// The first clause falsified by the model of the last `Sat` call, when
+    /// [`SolverConfig::verify_model`] is set. The model is kept as found so
+    /// it can be inspected.
+    pub fn verify_error(&self) -> Option<&UnsatisfiedClause> {
+        self.verify_error.as_ref()
+    }
+    /// The satisfying assignment found by the last call to [`Self::solve`],
+    /// one literal per variable, or `None` if the formula wasn't found `Sat`.
+    pub fn model(&self) -> Option<Vec<Lit>> {
+        match self.state {
+            SolveState::Sat => Some(self.model_iter().collect()),
+            _ => None,
+        }
+    }
+    /// Iterates over the literals of the satisfying assignment, ordered by
+    /// variable. Yields nothing unless the formula was found `Sat`.
+    pub fn model_iter(&self) -> impl Iterator<Item = Lit> + '_ {
+        let unused = self.model.len()..self.model_var_count;
+        self.model
+            .iter()
+            .copied()
+            .chain(unused.map(|index| Lit::from_index(index, false)))
+    }
+    /// The value of `lit` in the satisfying assignment, `None` if the formula
+    /// wasn't found `Sat` or the variable is unknown to the solver.
+    pub fn value(&self, lit: Lit) -> Option<bool> {
+        match self.model.get(lit.index()) {
+            Some(model) => Some(*model == lit),
+            None => (lit.index() < self.model_var_count).then(|| lit.is_negative()),
+        }
+    }
+    /// The value of `var` in the satisfying assignment, see [`Self::value`].
+    pub fn var_value(&self, var: Var) -> Option<bool> {
+        self.value(var.lit(true))
+    }
+    /// The assumptions of the last call to [`Self::solve_with_assumptions`]
+    /// that together made the formula unsatisfiable.
+    ///
+    /// Empty if that call wasn't `Unsat`, or if the formula is unsatisfiable
+    /// without any assumptions.
+    pub fn failed_assumptions(&self) -> &[Lit] {
+        &self.failed_assumptions
+    }
+    /// Writes every clause the solver knows, including the clauses learnt so
+    /// far, in DIMACS CNF format.
+    pub fn write_dimacs<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.clause_db.write_dimacs(&mut writer, self.var_count)
+    }
+    /// Whether the model satisfies every clause the solver knows, learnt
+    /// clauses included. False unless the formula was found `Sat`.
+    pub fn check_satisfied(&self) -> bool {
+        let is_true = |lit: &Lit| self.value(*lit) == Some(true);
+        let db = &self.clause_db;
+        self.state == SolveState::Sat
+            && !db.has_empty_clause
+            && db.assign_clauses.iter().all(is_true)
+            && db
+                .binary_clauses
+                .iter()
+                .all(|clause| clause.iter().any(is_true))
+            && db
+                .long_clauses
+                .iter()
+                .all(|clause| clause.iter().any(is_true))
     }
     pub fn get_prop_reason(&self, lit: &Lit) -> Option<Vec<Lit>> {
+        if lit.index() >= self.assignment.len() {
+            // declared but unused, so never propagated
+            return None;
+        }
         match self.state {
             SolveState::Sat => match self.prop_graph.get_node(lit).reason {
                 PropReason::Unit => None,
-                PropReason::Binary(l) => Some(l.to_vec()),
+                PropReason::Binary(l, _) => Some(l.to_vec()),
                 PropReason::Long(r) => match r {
-                    ClauseRef::Binary(_) => None,
+                    ClauseRef::Binary(index) => {
+                        let [m, n] = self.clause_db.binary_clauses[index];
+                        Some(vec![if m.index() == lit.index() { !n } else { !m }])
+                    }
                     ClauseRef::Long(index) => {
                         let mut res = vec![];
-                        for ele in &self.clause_db.long_clauses[index] {
+                        for ele in self.clause_db.long_clauses[index].iter() {
                             if ele.index() != lit.index() {
                                 res.push(!*ele);
                             }
@@ -143,3 +557,27 @@ impl Solver {
         }
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_error() {
+        let mut solver = Solver::with_config(SolverConfig {
+            verify_model: true,
+            ..Default::default()
+        });
+        let lit = Lit::from_dimacs(1, true);
+        solver.add_clause(&[lit]);
+        // a clause the search never saw, so the model can't satisfy it
+        solver.original.as_mut().unwrap().add_clause(&[!lit]);
+        assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Sat);
+        let clause = solver.verify_error().unwrap().clone();
+        assert_eq!(clause.id, 2);
+        assert_eq!(solver.model(), Some(vec![lit]));
+        assert!(matches!(
+            solver.try_solve_with_assumptions(&[]),
+            Err(RsatError::InvalidModel(error)) if error == clause
+        ));
+    }
+}
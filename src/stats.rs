@@ -0,0 +1,111 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::limit::CLOCK_INTERVAL;
+
+/// Counters of what the solver did, summed over every call to solve.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SolverStats {
+    pub decisions: u64,
+    /// Number of assigned literals whose consequences were propagated.
+    pub propagations: u64,
+    pub conflicts: u64,
+    pub restarts: u64,
+    pub learnt_clauses: u64,
+    /// Literals of the learnt clauses before minimization.
+    pub learnt_literals_unminimized: u64,
+    /// Literals of the learnt clauses as they were added.
+    pub learnt_literals: u64,
+    /// Reductions of the learnt clause database.
+    pub reductions: u64,
+    /// Learnt clauses deleted by reductions.
+    pub deleted_clauses: u64,
+    pub solve_time: Duration,
+    /// Estimated from a sample of the propagations, like `analyze_time`.
+    pub propagate_time: Duration,
+    /// Time spent analyzing conflicts, including minimization.
+    pub analyze_time: Duration,
+    pub reduce_time: Duration,
+}
+impl fmt::Display for SolverStats {
+    /// One `c ` prefixed line per counter, as DIMACS comments.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let per_second = |count: u64| {
+            let seconds = self.solve_time.as_secs_f64();
+            if seconds > 0.0 {
+                count as f64 / seconds
+            } else {
+                0.0
+            }
+        };
+        let counters = [
+            ("decisions", self.decisions),
+            ("propagations", self.propagations),
+            ("conflicts", self.conflicts),
+        ];
+        for (name, count) in counters {
+            writeln!(
+                f,
+                "c {:<18} {:>12} ({:.0} / s)",
+                name,
+                count,
+                per_second(count)
+            )?;
+        }
+        writeln!(f, "c {:<18} {:>12}", "restarts", self.restarts)?;
+        writeln!(f, "c {:<18} {:>12}", "learnt clauses", self.learnt_clauses)?;
+        let removed = self
+            .learnt_literals_unminimized
+            .saturating_sub(self.learnt_literals);
+        let removed_percent = if self.learnt_literals_unminimized > 0 {
+            100.0 * removed as f64 / self.learnt_literals_unminimized as f64
+        } else {
+            0.0
+        };
+        writeln!(
+            f,
+            "c {:<18} {:>12} ({:.1} % minimized away)",
+            "learnt literals", self.learnt_literals, removed_percent
+        )?;
+        writeln!(f, "c {:<18} {:>12}", "reductions", self.reductions)?;
+        writeln!(
+            f,
+            "c {:<18} {:>12}",
+            "deleted clauses", self.deleted_clauses
+        )?;
+        let times = [
+            ("solve time", self.solve_time),
+            ("  propagation", self.propagate_time),
+            ("  analysis", self.analyze_time),
+            ("  reduction", self.reduce_time),
+        ];
+        for (name, time) in times {
+            writeln!(f, "c {:<18} {:>12.3} s", name, time.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+/// Times one in every [`CLOCK_INTERVAL`] calls of a phase of the search and
+/// counts it for all of them.
+#[derive(Debug, Default)]
+pub(crate) struct SampledTimer {
+    ticks: u32,
+}
+impl SampledTimer {
+    /// Called before the phase, returns the start time if it's sampled.
+    #[inline]
+    pub(crate) fn start(&mut self) -> Option<Instant> {
+        self.ticks = self.ticks.wrapping_add(1);
+        (self.ticks % CLOCK_INTERVAL == 1).then(Instant::now)
+    }
+    /// Called after the phase with what [`Self::start`] returned.
+    #[inline]
+    pub(crate) fn stop(start: Option<Instant>, total: &mut Duration) {
+        if let Some(start) = start {
+            *total += start.elapsed() * CLOCK_INTERVAL;
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Logging of the search through the [`log`](https://docs.rs/log) crate.
+//!
+//! Without the `log` feature the macros expand to nothing, so the search
+//! pays nothing for them. With it, decisions and conflicts are logged at
+//! `trace`, learnt clauses, restarts and reductions at `debug` and solve
+//! results at `info`. Nothing is printed unless the application installs a
+//! logger.
+
+macro_rules! rsat_log {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        ::log::$level!($($arg)+);
+        #[cfg(not(feature = "log"))]
+        if false {
+            // keeps the arguments type checked and used
+            let _ = format_args!($($arg)+);
+        }
+    }};
+}
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        rsat_log!(trace, $($arg)+)
+    };
+}
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        rsat_log!(debug, $($arg)+)
+    };
+}
+macro_rules! info {
+    ($($arg:tt)+) => {
+        rsat_log!(info, $($arg)+)
+    };
+}
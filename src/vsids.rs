@@ -0,0 +1,159 @@
+/// Variable activities for the EVSIDS decision heuristic.
+///
+/// Variables in conflicts get their activity bumped by an increment that
+/// grows after every conflict, which decays the old bumps relative to the new
+/// ones. Unassigned variables are kept in a binary max-heap on activity.
+#[derive(Debug)]
+pub(crate) struct Vsids {
+    activity: Vec<f64>,
+    heap: Vec<usize>,
+    /// Position of each variable in `heap`, `NOT_IN_HEAP` if it's not there.
+    position: Vec<usize>,
+    increment: f64,
+    decay: f64,
+}
+const NOT_IN_HEAP: usize = usize::MAX;
+const RESCALE_LIMIT: f64 = 1e100;
+impl Default for Vsids {
+    fn default() -> Self {
+        Vsids {
+            activity: Vec::new(),
+            heap: Vec::new(),
+            position: Vec::new(),
+            increment: 1.0,
+            decay: 0.95,
+        }
+    }
+}
+impl Vsids {
+    pub(crate) fn resize(&mut self, var_count: usize) {
+        let old_count = self.activity.len();
+        self.activity.resize(var_count, 0.0);
+        self.position.resize(var_count, NOT_IN_HEAP);
+        for var in old_count..var_count {
+            self.push(var);
+        }
+    }
+    #[inline]
+    pub(crate) fn bump(&mut self, var: usize) {
+        self.activity[var] += self.increment;
+        if self.activity[var] > RESCALE_LIMIT {
+            for activity in self.activity.iter_mut() {
+                *activity /= RESCALE_LIMIT;
+            }
+            self.increment /= RESCALE_LIMIT;
+        }
+        if self.position[var] != NOT_IN_HEAP {
+            self.sift_up(self.position[var]);
+        }
+    }
+    /// Called once per conflict, after bumping the conflict's variables.
+    #[inline]
+    pub(crate) fn decay(&mut self) {
+        self.increment /= self.decay;
+    }
+    /// Makes `var` available for decisions again, e.g. after backtracking.
+    #[inline]
+    pub(crate) fn push(&mut self, var: usize) {
+        if self.position[var] != NOT_IN_HEAP {
+            return;
+        }
+        self.position[var] = self.heap.len();
+        self.heap.push(var);
+        self.sift_up(self.heap.len() - 1);
+    }
+    /// Removes and returns the variable with the highest activity.
+    pub(crate) fn pop(&mut self) -> Option<usize> {
+        let top = *self.heap.first()?;
+        let last = self.heap.pop().unwrap();
+        self.position[top] = NOT_IN_HEAP;
+        if !self.heap.is_empty() {
+            self.heap[0] = last;
+            self.position[last] = 0;
+            self.sift_down(0);
+        }
+        Some(top)
+    }
+    /// Ties are broken towards the lower variable index.
+    #[inline]
+    fn before(&self, a: usize, b: usize) -> bool {
+        let (activity_a, activity_b) = (self.activity[a], self.activity[b]);
+        activity_a > activity_b || (activity_a == activity_b && a < b)
+    }
+    fn sift_up(&mut self, mut pos: usize) {
+        let var = self.heap[pos];
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if !self.before(var, self.heap[parent]) {
+                break;
+            }
+            self.heap[pos] = self.heap[parent];
+            self.position[self.heap[pos]] = pos;
+            pos = parent;
+        }
+        self.heap[pos] = var;
+        self.position[var] = pos;
+    }
+    fn sift_down(&mut self, mut pos: usize) {
+        let var = self.heap[pos];
+        loop {
+            let left = 2 * pos + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.heap.len() && self.before(self.heap[right], self.heap[left])
+            {
+                right
+            } else {
+                left
+            };
+            if !self.before(self.heap[child], var) {
+                break;
+            }
+            self.heap[pos] = self.heap[child];
+            self.position[self.heap[pos]] = pos;
+            pos = child;
+        }
+        self.heap[pos] = var;
+        self.position[var] = pos;
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heap_order() {
+        let mut vsids = Vsids::default();
+        vsids.resize(6);
+        vsids.bump(4);
+        vsids.decay();
+        vsids.bump(2);
+        vsids.bump(5);
+        vsids.decay();
+        vsids.bump(2);
+        let order: Vec<usize> = std::iter::from_fn(|| vsids.pop()).collect();
+        assert_eq!(order, vec![2, 5, 4, 0, 1, 3]);
+        vsids.push(3);
+        vsids.push(2);
+        vsids.push(3);
+        assert_eq!(vsids.pop(), Some(2));
+        assert_eq!(vsids.pop(), Some(3));
+        assert_eq!(vsids.pop(), None);
+    }
+    #[test]
+    fn test_rescale() {
+        let mut vsids = Vsids::default();
+        vsids.resize(2);
+        for _ in 0..10000 {
+            vsids.bump(1);
+            vsids.decay();
+        }
+        vsids.bump(0);
+        assert!(vsids.activity.iter().all(|a| *a <= RESCALE_LIMIT));
+        assert!(vsids.activity[0] > 0.0);
+        assert_eq!(vsids.pop(), Some(1));
+        assert_eq!(vsids.pop(), Some(0));
+    }
+}
@@ -1,12 +1,9 @@
 use std::{
-    mem::replace,
+    mem::take,
     ops::{Deref, DerefMut},
 };
 
-use crate::{
-    lit::Lit,
-    solver::{ClauseRef, Solver},
-};
+use crate::{lit::Lit, solver::ClauseRef};
 #[derive(Debug, Default, Clone)]
 pub(crate) struct Watchlists {
     lists: Vec<Watchlist>,
@@ -53,7 +50,7 @@ impl Watchlists {
     #[inline]
     pub(crate) fn pop_watch_list(&mut self, lit: Lit) -> Watchlist {
         let watch_list = &mut self.lists[lit.code()];
-        replace(watch_list, Watchlist::default())
+        take(watch_list)
     }
 
     #[inline]
@@ -61,6 +58,24 @@ impl Watchlists {
         self.lists[lit.code()] = watch_list;
     }
 }
+impl Watchlists {
+    /// Points the watches of long clauses to their index after the clause
+    /// database was compacted, dropping the watches of deleted clauses.
+    pub(crate) fn remap_long(&mut self, remap: &[Option<usize>]) {
+        for watch_list in self.lists.iter_mut() {
+            watch_list.list.retain_mut(|watch| match watch.cref {
+                ClauseRef::Binary(_) => true,
+                ClauseRef::Long(index) => match remap[index] {
+                    Some(new_index) => {
+                        watch.cref = ClauseRef::Long(new_index);
+                        true
+                    }
+                    None => false,
+                },
+            });
+        }
+    }
+}
 impl Watch {
     pub(crate) fn new(clause_ref: ClauseRef, blocking: Lit) -> Self {
         Watch {
@@ -81,17 +96,3 @@ impl DerefMut for Watchlist {
         &mut self.list
     }
 }
-impl Solver {
-    pub(crate) fn generate_watch(&mut self) {
-        for index in 0..self.clause_db.binary_clauses.len() {
-            let clause = self.clause_db.binary_clauses[index].as_ref();
-            self.watch_lists
-                .watch_clause(ClauseRef::Binary(index), clause[0], clause[1]);
-        }
-        for index in 0..self.clause_db.long_clauses.len() {
-            let clause = &mut self.clause_db.long_clauses[index];
-            self.watch_lists
-                .watch_clause(ClauseRef::Long(index), clause[0], clause[1]);
-        }
-    }
-}
@@ -0,0 +1,64 @@
+#![cfg(feature = "cli")]
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+use rsat::cnf::CnfFormula;
+
+fn run_rsat(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rsat"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+fn model_values(stdout: &str) -> Vec<isize> {
+    stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("v "))
+        .flat_map(|line| line.split_whitespace().map(|x| x.parse().unwrap()))
+        .collect()
+}
+#[test]
+fn test_cli_sat() {
+    let path = "tests/cnfs/test.cnf";
+    let output = run_rsat(&[path], b"");
+    assert_eq!(output.status.code(), Some(10));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.lines().any(|line| line == "s SATISFIABLE"));
+    assert!(stdout.lines().all(|line| line.len() <= 80));
+    assert!(stdout.lines().any(|line| line.starts_with("c conflicts")));
+    assert!(stdout
+        .lines()
+        .all(|line| ["c ", "s ", "v "].iter().any(|p| line.starts_with(p))));
+    let values = model_values(&stdout);
+    assert_eq!(values.last(), Some(&0));
+    let formula = CnfFormula::from_dimacs_file(path).unwrap();
+    for clause in formula.iter() {
+        assert!(clause.iter().any(|lit| {
+            let value = lit.to_dimacs() as isize;
+            values.contains(&if lit.is_negative() { -value } else { value })
+        }));
+    }
+}
+#[test]
+fn test_cli_unsat_stdin() {
+    let input = b"p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n";
+    let output = run_rsat(&[], input);
+    assert_eq!(output.status.code(), Some(20));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.lines().any(|line| line == "s UNSATISFIABLE"));
+    assert!(model_values(&stdout).is_empty());
+}
+#[test]
+fn test_cli_parse_error() {
+    let output = run_rsat(&["-"], b"p cnf 1 1\n2 0\n");
+    assert!(!output.status.success());
+    assert_ne!(output.status.code(), Some(10));
+    assert_ne!(output.status.code(), Some(20));
+}
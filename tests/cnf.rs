@@ -1,35 +1,22 @@
-use anyhow::Result;
-use rsat::{cnf::CnfFormula, lit::Lit, solver::Solver};
+use rsat::{
+    cnf::{parse_dimacs, AddClauseOutcome, CnfFormula},
+    error::{RsatError, UnsatisfiedClause},
+    lit::{Lit, Var},
+    solver::Solver,
+};
 
 use std::{
-    fs::File,
-    io::{BufRead, BufReader},
+    panic::{self, AssertUnwindSafe},
     path::Path,
 };
-fn parse_cnf<P: AsRef<Path>>(path: P) -> Result<CnfFormula> {
-    let mut lines = BufReader::new(File::open(path)?).lines();
-    lines.next();
-    let mut cnf = CnfFormula::default();
-    for line in lines {
-        let mut clause = Vec::new();
-        if let Ok(c) = line {
-            for ele in c.split_whitespace() {
-                if let Ok(s) = ele.parse::<isize>() {
-                    if s != 0 {
-                        clause.push(Lit::from_dimacs(s.abs() as usize, s > 0));
-                    }
-                }
-            }
-        }
-        cnf.add_clause(clause.as_slice());
-    }
-    Ok(cnf)
+fn parse_cnf<P: AsRef<Path>>(path: P) -> CnfFormula {
+    CnfFormula::from_dimacs_file(path).unwrap()
 }
 #[test]
 fn test_cnf() {
     let path = Path::new("tests/cnfs/sgen1_sat_90_0.cnf");
-    let cnf = parse_cnf(path).unwrap();
-    dbg!(Solver::new(true)
+    let cnf = parse_cnf(path);
+    assert!(Solver::new(true)
         .add_formula(&cnf)
         .solve()
         .check_satisfied());
@@ -49,9 +36,129 @@ fn test_add_clause() {
     assert_eq!(formula_iter.next(), Some([b, c].as_ref()));
     assert_eq!(formula_iter.next(), Some([!a, b].as_ref()));
     assert_eq!(formula_iter.next(), None);
+    assert_eq!(formula.tautologies(), 1);
+    assert_eq!(formula.duplicate_literals(), 1);
+}
+#[test]
+fn test_try_add_clause() {
+    let mut formula = CnfFormula::default();
+    let a = Lit::from_dimacs(1, true);
+    let b = Lit::from_dimacs(2, true);
+    let c = Lit::from_dimacs(5, true);
+    assert_eq!(formula.try_add_clause(&[a, b]), AddClauseOutcome::Added);
+    assert_eq!(
+        formula.try_add_clause(&[b, a, b, b, !a]),
+        AddClauseOutcome::Tautology
+    );
+    assert_eq!(
+        formula.try_add_clause(&[c, !c]),
+        AddClauseOutcome::Tautology
+    );
+    assert_eq!(
+        formula.try_add_clause(&[!b, !b, a, !b]),
+        AddClauseOutcome::Simplified { duplicates: 2 }
+    );
+    assert_eq!(formula.try_add_clause(&[]), AddClauseOutcome::Added);
+    assert_eq!(formula.tautologies(), 2);
+    assert_eq!(formula.duplicate_literals(), 2);
+    // dropped tautologies don't count towards the variables
+    assert_eq!(formula.var_count(), 2);
+    let clauses: Vec<_> = formula.iter().collect();
+    assert_eq!(clauses, vec![&[a, b][..], &[!b, a], &[]]);
+}
+#[test]
+fn test_new_var() {
+    let mut formula = CnfFormula::new();
+    assert_eq!(formula.var_count(), 0);
+    let a = formula.new_lit();
+    let b = formula.new_var();
+    assert_eq!(a, Lit::from_dimacs(1, true));
+    assert_eq!(b.to_dimacs(), 2);
+    assert_eq!(formula.var_count(), 2);
+    assert_eq!(formula.iter().count(), 0);
+    formula.add_clause(&[Lit::from_dimacs(4, false)]);
+    assert_eq!(formula.new_var().to_dimacs(), 5);
+    formula.declare_vars(3);
+    assert_eq!(formula.var_count(), 5);
+    formula.declare_vars(8);
+    assert_eq!(formula.new_lit(), Lit::from_dimacs(9, true));
+
+    assert!(matches!(
+        formula.try_declare_vars(Lit::MAX_VAR + 1),
+        Err(RsatError::VarOutOfRange { .. })
+    ));
+    assert_eq!(formula.var_count(), 9);
+    formula.declare_vars(Lit::MAX_VAR);
+    // no variable is left, and the formula stays as it was
+    let new_var = panic::catch_unwind(AssertUnwindSafe(|| formula.new_var()));
+    assert!(new_var.is_err());
+    assert_eq!(formula.var_count(), Lit::MAX_VAR);
 }
 #[test]
 fn test_postive_negtive() {
     assert_eq!(Lit::from_dimacs(1, true).to_dimacs(), 1);
     assert_eq!((!Lit::from_dimacs(1, true)).to_dimacs(), 1);
 }
+#[test]
+fn test_var() {
+    let var = Var::from_dimacs(3);
+    assert_eq!(var, Var::from_index(2));
+    assert_eq!(var.to_dimacs(), 3);
+    assert_eq!(var.lit(true), Lit::from_dimacs(3, true));
+    assert_eq!(var.lit(false), !var.lit(true));
+    assert!(var.lit(false).is_negative());
+    assert_eq!(var.lit(false).var(), var);
+    assert_eq!(Lit::from_var(var, true), var.lit(true));
+    assert_eq!(Lit::from_dimacs(8, true).var().index(), 7);
+    assert_eq!(format!("{:?}", var), "3");
+}
+#[test]
+fn test_lit_range() {
+    #[cfg(not(feature = "wide-literals"))]
+    {
+        assert_eq!(std::mem::size_of::<Lit>(), 4);
+        assert_eq!(Lit::MAX_VAR, 1 << 31);
+    }
+    let max = Var::from_index(Lit::MAX_INDEX).lit(false);
+    assert_eq!(max.var().index(), Lit::MAX_INDEX);
+    assert_eq!(max.to_dimacs(), Lit::MAX_VAR);
+    assert!(max.is_negative());
+    assert_eq!((!max).var(), Var::from_index(Lit::MAX_INDEX));
+    assert_eq!(Var::try_from_index(4).unwrap(), Var::from_dimacs(5));
+    assert!(Var::try_from_index(Lit::MAX_INDEX + 1).is_err());
+    assert!(Lit::try_from_dimacs(Lit::MAX_VAR + 1, true).is_err());
+}
+#[test]
+#[should_panic(expected = "out of range")]
+fn test_lit_out_of_range() {
+    Var::from_index(Lit::MAX_INDEX + 1);
+}
+#[test]
+fn test_evaluate() {
+    let mut formula = CnfFormula::default();
+    let a = Lit::from_dimacs(1, true);
+    let b = Lit::from_dimacs(2, true);
+    let c = Lit::from_dimacs(3, true);
+    formula.add_clause(&[a, b]);
+    formula.add_clause(&[!a, c]);
+    formula.add_clause(&[!b, !c]);
+    assert_eq!(formula.evaluate(&[a, !b, c]), Ok(()));
+    assert_eq!(
+        formula.evaluate(&[a, b, c]),
+        Err(UnsatisfiedClause {
+            id: 3,
+            clause: vec![!b, !c],
+        })
+    );
+    let unassigned = formula.evaluate(&[!a]).unwrap_err();
+    assert_eq!(unassigned.id, 1);
+    assert_eq!(
+        unassigned.to_string(),
+        "clause 1 `1 2 0` is not satisfied by the model"
+    );
+
+    // ids count the dropped tautology, as in the input
+    let input = "p cnf 2 3\n1 -1 0\n1 0\n2 0\n";
+    let formula = parse_dimacs(input.as_bytes()).unwrap();
+    assert_eq!(formula.evaluate(&[a, !b]).unwrap_err().id, 3);
+}
@@ -0,0 +1,81 @@
+// every test file only uses some of the helpers
+#![allow(dead_code)]
+
+use rsat::{
+    cnf::CnfFormula,
+    config::SolverConfig,
+    lit::{Lit, Var},
+    solver::{SolveState, Solver},
+};
+
+/// The unsatisfiable formula putting `holes + 1` pigeons into `holes` holes,
+/// hard for resolution so it takes a while to refute.
+pub fn pigeon_hole(holes: usize) -> CnfFormula {
+    let var = |pigeon: usize, hole: usize| Var::from_index(pigeon * holes + hole).lit(true);
+    let mut formula = CnfFormula::new();
+    for pigeon in 0..=holes {
+        let clause: Vec<Lit> = (0..holes).map(|hole| var(pigeon, hole)).collect();
+        formula.add_clause(&clause);
+    }
+    for hole in 0..holes {
+        for a in 0..=holes {
+            for b in a + 1..=holes {
+                formula.add_clause(&[!var(a, hole), !var(b, hole)]);
+            }
+        }
+    }
+    formula
+}
+
+/// A tiny random number generator, so the tests are reproducible.
+pub struct Lcg(pub u64);
+impl Lcg {
+    pub fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+    pub fn lit(&mut self, var_count: usize) -> Lit {
+        Lit::from_var(Var::from_index(self.next(var_count)), self.next(2) == 0)
+    }
+    /// `count` random clauses of `len` literals over `var_count` variables.
+    pub fn clauses(&mut self, var_count: usize, count: usize, len: usize) -> Vec<Vec<Lit>> {
+        (0..count)
+            .map(|_| (0..len).map(|_| self.lit(var_count)).collect())
+            .collect()
+    }
+}
+
+/// Whether some assignment of the first `var_count` variables satisfies the
+/// clauses and the assumptions.
+pub fn brute_force(clauses: &[Vec<Lit>], assumptions: &[Lit], var_count: usize) -> bool {
+    (0..1u32 << var_count).any(|bits| {
+        let is_true = |lit: &Lit| ((bits >> lit.var().index()) & 1 == 1) == lit.is_positive();
+        assumptions.iter().all(is_true) && clauses.iter().all(|c| c.iter().any(is_true))
+    })
+}
+
+/// Solves 20 random 3-SAT formulas with each of `configs` and checks the
+/// answers against [`brute_force`].
+pub fn check_against_brute_force(
+    rng: &mut Lcg,
+    var_count: usize,
+    clause_count: usize,
+    configs: impl IntoIterator<Item = SolverConfig>,
+) {
+    let configs: Vec<SolverConfig> = configs.into_iter().collect();
+    for _ in 0..20 {
+        let clauses = rng.clauses(var_count, clause_count, 3);
+        let expected = brute_force(&clauses, &[], var_count);
+        for config in &configs {
+            let mut solver = Solver::with_config(config.clone());
+            for clause in &clauses {
+                solver.add_clause(clause);
+            }
+            let state = solver.solve_with_assumptions(&[]);
+            assert_eq!(state == SolveState::Sat, expected, "{:?}", config);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use rsat::{
+    cnf::{parse_dimacs, CnfFormula},
+    error::DimacsError,
+    lit::Lit,
+    solver::Solver,
+};
+
+fn lits(clause: &[isize]) -> Vec<Lit> {
+    clause
+        .iter()
+        .map(|x| Lit::from_dimacs(x.unsigned_abs(), *x > 0))
+        .collect()
+}
+#[test]
+fn test_parse_dimacs() {
+    let input = "c leading comment\n\
+                 p cnf 4 3\n\
+                 1 -2 0 c trailing comment\n\
+                 c comment between clauses\n\
+                 3\n\
+                 -4 2\n\
+                 0 -1 0\n";
+    let formula = parse_dimacs(input.as_bytes()).unwrap();
+    let clauses: Vec<Vec<Lit>> = formula.iter().map(|c| c.to_vec()).collect();
+    assert_eq!(
+        clauses,
+        vec![lits(&[1, -2]), lits(&[3, -4, 2]), lits(&[-1])]
+    );
+    // variables declared by the header count even if no clause uses them
+    let formula = parse_dimacs("p cnf 7 1\n1 -2 0\n".as_bytes()).unwrap();
+    assert_eq!(formula.var_count(), 7);
+    let solver = Solver::default().add_formula(&formula).solve();
+    assert_eq!(solver.model().unwrap().len(), 7);
+}
+#[test]
+fn test_parse_dimacs_files() {
+    for path in [
+        "tests/cnfs/sgen1_sat_90_0.cnf",
+        "tests/cnfs/sgen1_unsat_57_0.cnf",
+        "tests/cnfs/test.cnf",
+    ] {
+        let file = std::fs::File::open(path).unwrap();
+        assert!(CnfFormula::from_dimacs_reader(file).is_ok(), "{}", path);
+    }
+}
+#[test]
+fn test_parse_dimacs_percent_terminator() {
+    let formula = parse_dimacs("p cnf 2 1\n1 2 0\n%\n0\n".as_bytes()).unwrap();
+    assert_eq!(formula.iter().count(), 1);
+}
+#[test]
+fn test_parse_dimacs_errors() {
+    let parse = |input: &str| parse_dimacs(input.as_bytes()).unwrap_err();
+    assert!(matches!(parse("1 2 0\n"), DimacsError::MissingHeader));
+    assert!(matches!(
+        parse("c only comments\n"),
+        DimacsError::MissingHeader
+    ));
+    assert!(matches!(
+        parse("p cnf 2\n"),
+        DimacsError::InvalidHeader { line: 1, column: 1 }
+    ));
+    assert!(matches!(
+        parse("p dnf 2 1\n"),
+        DimacsError::InvalidHeader { line: 1, column: 3 }
+    ));
+    let too_many_vars = format!("p cnf {} 1\n", Lit::MAX_VAR + 1);
+    assert!(matches!(
+        parse(&too_many_vars),
+        DimacsError::InvalidHeader { line: 1, column: 7 }
+    ));
+    assert!(matches!(
+        parse("p cnf 2 1\np cnf 2 1\n"),
+        DimacsError::DuplicateHeader { line: 2, column: 1 }
+    ));
+    assert!(matches!(
+        parse("p cnf 2 1\n1 x2 0\n"),
+        DimacsError::InvalidLiteral {
+            line: 2,
+            column: 3,
+            ..
+        }
+    ));
+    assert!(matches!(
+        parse("p cnf 2 1\n1  -3 0\n"),
+        DimacsError::VarOutOfRange {
+            line: 2,
+            column: 4,
+            var: 3,
+            var_count: 2
+        }
+    ));
+    assert!(matches!(
+        parse("p cnf 2 1\n1 2\n"),
+        DimacsError::UnterminatedClause { line: 2 }
+    ));
+    assert!(matches!(
+        parse("p cnf 2 2\n1 2 0\n"),
+        DimacsError::ClauseCountMismatch {
+            expected: 2,
+            found: 1
+        }
+    ));
+}
+#[test]
+fn test_write_dimacs() {
+    let input = "p cnf 5 3\n1 -2 0\n3 -4 2 0\n-5 0\n";
+    let formula = parse_dimacs(input.as_bytes()).unwrap();
+    let mut output = Vec::new();
+    formula.write_dimacs(&mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), input);
+    let mut output = Vec::new();
+    CnfFormula::new().write_dimacs(&mut output).unwrap();
+    assert_eq!(output, b"p cnf 0 0\n");
+}
+#[test]
+fn test_write_solver_dimacs() {
+    let file = std::fs::File::open("tests/cnfs/test.cnf").unwrap();
+    let formula = CnfFormula::from_dimacs_reader(file).unwrap();
+    let solver = Solver::default().add_formula(&formula).solve();
+    let mut output = Vec::new();
+    solver.write_dimacs(&mut output).unwrap();
+    let dumped = parse_dimacs(output.as_slice()).unwrap();
+    assert_eq!(dumped.var_count(), formula.var_count());
+    assert!(dumped.iter().count() >= formula.iter().count());
+}
+#[cfg(any(feature = "gzip", feature = "xz", feature = "bzip2"))]
+fn assert_compressed_parse(compressed: &[u8]) {
+    let plain = std::fs::read("tests/cnfs/test.cnf").unwrap();
+    let expected = parse_dimacs(plain.as_slice()).unwrap();
+    let formula = CnfFormula::from_dimacs_reader(compressed).unwrap();
+    assert!(formula.iter().eq(expected.iter()));
+    // the magic bytes may arrive over several reads, e.g. from a pipe
+    let formula = CnfFormula::from_dimacs_reader(ByteReader(compressed)).unwrap();
+    assert!(formula.iter().eq(expected.iter()));
+}
+/// Returns a single byte per read.
+#[cfg(any(feature = "gzip", feature = "xz", feature = "bzip2"))]
+struct ByteReader<'a>(&'a [u8]);
+#[cfg(any(feature = "gzip", feature = "xz", feature = "bzip2"))]
+impl std::io::Read for ByteReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.0.len()).min(1);
+        buf[..len].copy_from_slice(&self.0[..len]);
+        self.0 = &self.0[len..];
+        Ok(len)
+    }
+}
+#[cfg(feature = "gzip")]
+#[test]
+fn test_parse_gzip() {
+    use std::io::Write;
+    let plain = std::fs::read("tests/cnfs/test.cnf").unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&plain).unwrap();
+    assert_compressed_parse(&encoder.finish().unwrap());
+}
+#[cfg(feature = "xz")]
+#[test]
+fn test_parse_xz() {
+    use std::io::Write;
+    let plain = std::fs::read("tests/cnfs/test.cnf").unwrap();
+    let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(&plain).unwrap();
+    assert_compressed_parse(&encoder.finish().unwrap());
+}
+#[cfg(feature = "bzip2")]
+#[test]
+fn test_parse_bzip2() {
+    use std::io::Write;
+    let plain = std::fs::read("tests/cnfs/test.cnf").unwrap();
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    encoder.write_all(&plain).unwrap();
+    assert_compressed_parse(&encoder.finish().unwrap());
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "log")]
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use rsat::{cnf::CnfFormula, lit::Lit, solver::Solver};
+
+static RECORDS: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+struct Recorder;
+impl Log for Recorder {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+    fn log(&self, record: &Record) {
+        let message = record.args().to_string();
+        RECORDS.lock().unwrap().push((record.level(), message));
+    }
+    fn flush(&self) {}
+}
+#[test]
+fn test_log_levels() {
+    log::set_logger(&Recorder).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+    let mut formula = CnfFormula::new();
+    for clause in [[1, 2], [-1, 2], [1, -2], [-1, -2]] {
+        let clause: Vec<_> = clause
+            .iter()
+            .map(|x: &isize| Lit::from_dimacs(x.unsigned_abs(), *x > 0))
+            .collect();
+        formula.add_clause(&clause);
+    }
+    Solver::default().add_formula(&formula).solve();
+    let records = RECORDS.lock().unwrap();
+    let logged = |level, prefix| {
+        records
+            .iter()
+            .any(|(l, message)| *l == level && message.starts_with(prefix))
+    };
+    assert!(logged(Level::Trace, "decide"));
+    assert!(logged(Level::Trace, "conflict"));
+    assert!(logged(Level::Debug, "learn"));
+    assert!(logged(Level::Info, "solved as Unsat"));
+}
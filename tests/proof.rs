@@ -0,0 +1,436 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use rsat::{
+    cnf::{parse_dimacs, CnfFormula},
+    config::{MinimizeMode, SolverConfig},
+    error::{ProofError, RsatError},
+    lit::Lit,
+    proof::{
+        check::{check_drat, check_lrat, check_proof},
+        ProofFormat,
+    },
+    solver::{SolveState, Solver},
+};
+
+mod common;
+use common::{pigeon_hole, Lcg};
+
+/// A proof sink the test can still read after handing it to the solver.
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+struct FailingWriter;
+impl Write for FailingWriter {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("disk full"))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn parse_text_drat(proof: &[u8]) -> Vec<(bool, Vec<Lit>)> {
+    String::from_utf8(proof.to_vec())
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let (delete, line) = match line.strip_prefix("d ") {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+            let mut numbers: Vec<isize> = line
+                .split_whitespace()
+                .map(|x| x.parse().unwrap())
+                .collect();
+            assert_eq!(numbers.pop(), Some(0));
+            let clause = numbers
+                .iter()
+                .map(|x| Lit::from_dimacs(x.unsigned_abs(), *x > 0))
+                .collect();
+            (delete, clause)
+        })
+        .collect()
+}
+fn same_clause(a: &[Lit], b: &[Lit]) -> bool {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+    a.sort();
+    b.sort();
+    a == b
+}
+/// Checks every added lemma is implied by the clauses before it, by brute
+/// force over all assignments.
+fn check_lemmas_implied(formula: &[Vec<Lit>], steps: &[(bool, Vec<Lit>)], var_count: usize) {
+    let mut clauses = formula.to_vec();
+    for (delete, lemma) in steps {
+        if *delete {
+            let position = clauses.iter().position(|c| same_clause(c, lemma)).unwrap();
+            clauses.swap_remove(position);
+            continue;
+        }
+        for bits in 0..1u32 << var_count {
+            let is_true = |lit: &Lit| ((bits >> lit.var().index()) & 1 == 1) == lit.is_positive();
+            if clauses.iter().all(|c| c.iter().any(is_true)) {
+                assert!(lemma.iter().any(is_true), "{:?} is not implied", lemma);
+            }
+        }
+        clauses.push(lemma.clone());
+    }
+}
+#[test]
+fn test_drat_proof() {
+    let formula = pigeon_hole(3);
+    let buf = SharedBuf::default();
+    let mut solver = Solver::with_config(SolverConfig {
+        reduce_interval: 1,
+        ..Default::default()
+    });
+    solver.write_proof(buf.clone(), ProofFormat::Drat);
+    let mut solver = solver.add_formula(&formula).solve();
+    assert_eq!(solver.state(), SolveState::Unsat);
+    solver.finish_proof().unwrap();
+    let steps = parse_text_drat(&buf.0.lock().unwrap());
+    assert_eq!(steps.last(), Some(&(false, vec![])));
+    let formula: Vec<Vec<Lit>> = formula.iter().map(|c| c.to_vec()).collect();
+    check_lemmas_implied(&formula, &steps, 12);
+}
+#[test]
+fn test_drat_proof_deletions() {
+    let formula = pigeon_hole(5);
+    let buf = SharedBuf::default();
+    let mut solver = Solver::with_config(SolverConfig {
+        reduce_interval: 1,
+        ..Default::default()
+    });
+    solver.write_proof(buf.clone(), ProofFormat::Drat);
+    let mut solver = solver.add_formula(&formula).solve();
+    assert_eq!(solver.state(), SolveState::Unsat);
+    solver.finish_proof().unwrap();
+    let steps = parse_text_drat(&buf.0.lock().unwrap());
+    assert!(steps.iter().any(|(delete, _)| *delete));
+    let mut lemmas = vec![];
+    for (delete, mut clause) in steps {
+        clause.sort();
+        if delete {
+            let position = lemmas.iter().position(|c| *c == clause);
+            assert!(position.is_some(), "{:?} was never added", clause);
+            lemmas.swap_remove(position.unwrap());
+        } else {
+            lemmas.push(clause);
+        }
+    }
+    assert!(lemmas.len() > 1);
+}
+#[test]
+fn test_binary_drat_proof() {
+    let text = SharedBuf::default();
+    let binary = SharedBuf::default();
+    for (buf, format) in [
+        (&text, ProofFormat::Drat),
+        (&binary, ProofFormat::BinaryDrat),
+    ] {
+        let formula = pigeon_hole(3);
+        let mut solver = Solver::default();
+        solver.write_proof(buf.clone(), format);
+        let mut solver = solver.add_formula(&formula).solve();
+        solver.finish_proof().unwrap();
+    }
+    let mut expected = vec![];
+    for (delete, clause) in parse_text_drat(&text.0.lock().unwrap()) {
+        expected.push(if delete { b'd' } else { b'a' });
+        for lit in clause {
+            let code = 2 * lit.to_dimacs() + lit.is_negative() as usize;
+            assert!(code < 0x80);
+            expected.push(code as u8);
+        }
+        expected.push(0);
+    }
+    assert_eq!(*binary.0.lock().unwrap(), expected);
+
+    let buf = SharedBuf::default();
+    let mut solver = Solver::default();
+    solver.write_proof(buf.clone(), ProofFormat::BinaryDrat);
+    solver.add_clause(&[Lit::from_dimacs(100, false)]);
+    solver.add_clause(&[Lit::from_dimacs(100, true)]);
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unsat);
+    solver.finish_proof().unwrap();
+    assert_eq!(*buf.0.lock().unwrap(), vec![b'a', 0]);
+}
+#[test]
+fn test_proof_write_error() {
+    let formula = pigeon_hole(2);
+    let mut solver = Solver::default();
+    solver.write_proof(FailingWriter, ProofFormat::Drat);
+    let mut solver = solver.add_formula(&formula).solve();
+    assert_eq!(solver.state(), SolveState::Unsat);
+    assert!(solver.finish_proof().is_err());
+    assert!(solver.finish_proof().is_ok());
+}
+/// Excludes every assignment of two variables.
+const ALL_ASSIGNMENTS: &str = "p cnf 2 4\n1 2 0\n1 -2 0\n-1 2 0\n-1 -2 0\n";
+#[test]
+fn test_check_solver_proofs() {
+    for (holes, format) in [
+        (4, ProofFormat::Drat),
+        (5, ProofFormat::Drat),
+        (5, ProofFormat::BinaryDrat),
+        (4, ProofFormat::Lrat),
+        (5, ProofFormat::Lrat),
+    ] {
+        let formula = pigeon_hole(holes);
+        let buf = SharedBuf::default();
+        let mut solver = Solver::with_config(SolverConfig {
+            reduce_interval: 1,
+            ..Default::default()
+        });
+        solver.write_proof(buf.clone(), format);
+        let mut solver = solver.add_formula(&formula).solve();
+        assert_eq!(solver.state(), SolveState::Unsat);
+        solver.finish_proof().unwrap();
+        let proof = buf.0.lock().unwrap();
+        check_proof(&formula, proof.as_slice(), format).unwrap();
+        let truncated = &proof[..proof.len() / 2];
+        assert!(check_proof(&formula, truncated, format).is_err());
+    }
+}
+#[test]
+fn test_check_solver_lrat_random() {
+    let var_count = 40;
+    let mut rng = Lcg(13);
+    let mut unsat_count = 0;
+    for round in 0..60 {
+        let mut formula = CnfFormula::new();
+        let units = rng.next(3);
+        let clauses = [(units, 1), (20, 2), (150, 3)]
+            .into_iter()
+            .flat_map(|(count, len)| rng.clauses(var_count, count, len));
+        for clause in clauses {
+            formula.add_clause(&clause);
+        }
+        let minimize = [
+            MinimizeMode::None,
+            MinimizeMode::Basic,
+            MinimizeMode::Recursive,
+        ][round % 3];
+        let buf = SharedBuf::default();
+        let mut solver = Solver::with_config(SolverConfig {
+            reduce_interval: 5,
+            minimize,
+            ..Default::default()
+        });
+        solver.write_proof(buf.clone(), ProofFormat::Lrat);
+        let mut solver = solver.add_formula(&formula).solve();
+        solver.finish_proof().unwrap();
+        if solver.state() == SolveState::Unsat {
+            unsat_count += 1;
+            check_lrat(&formula, buf.0.lock().unwrap().as_slice()).unwrap();
+        }
+    }
+    assert!(unsat_count > 10, "{}", unsat_count);
+}
+#[test]
+fn test_lrat_proof_format() {
+    let buf = SharedBuf::default();
+    let mut solver = Solver::default();
+    solver.write_proof(buf.clone(), ProofFormat::Lrat);
+    let [a, b] = [Lit::from_dimacs(1, true), Lit::from_dimacs(2, true)];
+    solver.add_clause(&[a, !a]);
+    solver.add_clause(&[a]);
+    solver.add_clause(&[!a, b]);
+    solver.add_clause(&[!a, !b]);
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unsat);
+    solver.finish_proof().unwrap();
+    let proof = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(proof, "5 2 0 2 3 0\n6 0 2 5 4 0\n");
+}
+#[test]
+fn test_check_drat() {
+    let formula = parse_dimacs(ALL_ASSIGNMENTS.as_bytes()).unwrap();
+    let check = |proof: &str| check_drat(&formula, proof.as_bytes(), ProofFormat::Drat);
+    check("c 3 is RAT but not RUP\n3 0\n1 0\nd 1 2 0\n0\n").unwrap();
+    check("1 0\n").unwrap();
+    assert!(matches!(
+        check("3 0\n-3 4 0\n0\n"),
+        Err(ProofError::NotImplied { step: 2 })
+    ));
+    assert!(matches!(
+        check("3 0\n"),
+        Err(ProofError::MissingEmptyClause)
+    ));
+    assert!(matches!(
+        check("d 1 3 0\n"),
+        Err(ProofError::UnknownClause { step: 1 })
+    ));
+    assert!(matches!(
+        check("1 0\n2 x 0\n"),
+        Err(ProofError::InvalidStep { step: 2 })
+    ));
+    assert!(matches!(
+        check("1 2"),
+        Err(ProofError::InvalidStep { step: 1 })
+    ));
+    assert!(matches!(
+        check_drat(&formula, &b"a\x02\x00\x00"[..], ProofFormat::BinaryDrat),
+        Err(ProofError::InvalidStep { step: 2 })
+    ));
+    check_drat(
+        &formula,
+        &b"a\x02\x00d\x02\x04\x00a\x00"[..],
+        ProofFormat::BinaryDrat,
+    )
+    .unwrap();
+    // variables past Lit::MAX_VAR are rejected instead of panicking
+    let too_big = Lit::MAX_VAR as u64 + 1;
+    assert!(matches!(
+        check(&format!("{} 0\n0\n", too_big)),
+        Err(ProofError::InvalidStep { step: 1 })
+    ));
+    let mut binary = vec![b'a'];
+    let mut code = too_big.saturating_mul(2);
+    while code >= 0x80 {
+        binary.push(code as u8 | 0x80);
+        code >>= 7;
+    }
+    binary.extend([code as u8, 0]);
+    assert!(matches!(
+        check_drat(&formula, binary.as_slice(), ProofFormat::BinaryDrat),
+        Err(ProofError::InvalidStep { step: 1 })
+    ));
+}
+#[test]
+fn test_check_drat_ignores_unit_deletions() {
+    let input = "p cnf 4 6\n\
+                 1 0\n\
+                 -1 2 0\n\
+                 -2 3 4 0\n\
+                 -2 3 -4 0\n\
+                 -2 -3 4 0\n\
+                 -2 -3 -4 0\n";
+    let formula = parse_dimacs(input.as_bytes()).unwrap();
+    let proof = "d 1 0\nd -1 2 0\n3 0\n0\n";
+    check_drat(&formula, proof.as_bytes(), ProofFormat::Drat).unwrap();
+}
+#[test]
+fn test_check_lrat() {
+    let formula = parse_dimacs(ALL_ASSIGNMENTS.as_bytes()).unwrap();
+    let check = |proof: &str| check_lrat(&formula, proof.as_bytes());
+    check("5 1 0 1 2 0\n5 d 1 2 0\n6 0 5 3 4 0\n").unwrap();
+    let rat = "c pivot 3 has no candidates, 6 is RAT on -3\n\
+               5 3 -1 0 0\n\
+               6 -3 1 0 -5 0\n\
+               7 1 0 1 2 0\n\
+               8 0 7 3 4 0\n";
+    check(rat).unwrap();
+    assert!(matches!(
+        check("5 3 0 0\n6 -3 1 0 -5 0\n"),
+        Err(ProofError::NotImplied { step: 2 })
+    ));
+    assert!(matches!(
+        check("5 1 0 1 3 0\n"),
+        Err(ProofError::NotImplied { step: 1 })
+    ));
+    assert!(matches!(
+        check("5 1 0 1 9 0\n"),
+        Err(ProofError::UnknownClause { step: 1 })
+    ));
+    assert!(matches!(
+        check("5 1 0 1 2 0\n"),
+        Err(ProofError::MissingEmptyClause)
+    ));
+    assert!(matches!(
+        check("5 1 0 1 2\n"),
+        Err(ProofError::InvalidStep { step: 1 })
+    ));
+    assert!(matches!(
+        check(&format!("5 {} 0 1 2 0\n", Lit::MAX_VAR as u64 + 1)),
+        Err(ProofError::InvalidStep { step: 1 })
+    ));
+
+    // the tautology keeps its id 2, as in the DIMACS input
+    let input = "p cnf 2 5\n1 2 0\n1 -1 0\n1 -2 0\n-1 2 0\n-1 -2 0\n";
+    let formula = parse_dimacs(input.as_bytes()).unwrap();
+    let ids: Vec<u64> = formula.iter_with_ids().map(|(id, _)| id).collect();
+    assert_eq!(ids, vec![1, 3, 4, 5]);
+    check_lrat(&formula, "6 1 0 1 3 0\n7 0 6 4 5 0\n".as_bytes()).unwrap();
+    assert!(matches!(
+        check_lrat(&formula, "6 1 0 1 2 0\n".as_bytes()),
+        Err(ProofError::UnknownClause { step: 1 })
+    ));
+}
+#[test]
+fn test_proof_solver_send() {
+    let formula = pigeon_hole(3);
+    let buf = SharedBuf::default();
+    let mut solver = Solver::default();
+    solver.write_proof(buf.clone(), ProofFormat::Drat);
+    let solver = solver.add_formula(&formula);
+    // the solver, proof sink included, can be moved to a worker thread
+    let mut solver = std::thread::spawn(move || solver.solve()).join().unwrap();
+    assert_eq!(solver.state(), SolveState::Unsat);
+    solver.finish_proof().unwrap();
+    let proof = buf.0.lock().unwrap();
+    check_drat(&formula, proof.as_slice(), ProofFormat::Drat).unwrap();
+}
+#[test]
+fn test_lrat_tautology_ids() {
+    // clause 2 is a tautology, the proof must still refer to the others by
+    // their position in the file
+    let input = "p cnf 2 5\n1 2 0\n1 -1 0\n1 -2 0\n-1 2 0\n-1 -2 0\n";
+    let formula = parse_dimacs(input.as_bytes()).unwrap();
+    let buf = SharedBuf::default();
+    let mut solver = Solver::default();
+    solver.write_proof(buf.clone(), ProofFormat::Lrat);
+    let mut solver = solver.add_formula(&formula).solve();
+    assert_eq!(solver.state(), SolveState::Unsat);
+    solver.finish_proof().unwrap();
+    let proof = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(proof, "6 1 0 1 3 0\n7 2 0 6 4 0\n8 0 6 7 5 0\n");
+    check_lrat(&formula, proof.as_bytes()).unwrap();
+}
+#[test]
+fn test_late_proof() {
+    let formula = pigeon_hole(3);
+    let mut solver = Solver::default().add_formula(&formula);
+    assert!(matches!(
+        solver.try_write_proof(SharedBuf::default(), ProofFormat::Lrat),
+        Err(RsatError::LateLratProof)
+    ));
+    // a DRAT proof only lists lemmas, so it can still be started
+    let buf = SharedBuf::default();
+    solver
+        .try_write_proof(buf.clone(), ProofFormat::Drat)
+        .unwrap();
+    let mut solver = solver.solve();
+    assert_eq!(solver.state(), SolveState::Unsat);
+    solver.finish_proof().unwrap();
+    let proof = buf.0.lock().unwrap();
+    check_drat(&formula, proof.as_slice(), ProofFormat::Drat).unwrap();
+}
+#[test]
+fn test_lrat_added_clause_ids() {
+    // clauses added before solving follow the ones of the formula
+    let formula = parse_dimacs(ALL_ASSIGNMENTS.as_bytes()).unwrap();
+    let clauses: Vec<&[Lit]> = formula.iter().collect();
+    let buf = SharedBuf::default();
+    let mut solver = Solver::default();
+    solver.write_proof(buf.clone(), ProofFormat::Lrat);
+    let mut head = CnfFormula::new();
+    for clause in &clauses[..3] {
+        head.add_clause(clause);
+    }
+    let mut solver = solver.add_formula(&head);
+    solver.add_clause(clauses[3]);
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unsat);
+    solver.finish_proof().unwrap();
+    let proof = buf.0.lock().unwrap();
+    check_lrat(&formula, proof.as_slice()).unwrap();
+}
@@ -0,0 +1,511 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use rsat::{
+    cnf::CnfFormula,
+    config::{Limits, MinimizeMode, PolarityMode, RestartPolicy, SolverConfig},
+    error::RsatError,
+    lit::{Lit, Var},
+    proof::ProofFormat,
+    solver::{SolveState, Solver},
+};
+
+mod common;
+use common::{brute_force, check_against_brute_force, pigeon_hole, Lcg};
+
+fn lit(number: isize) -> Lit {
+    Lit::from_dimacs(number.unsigned_abs(), number > 0)
+}
+fn formula(clauses: &[&[isize]]) -> CnfFormula {
+    let mut formula = CnfFormula::new();
+    for clause in clauses {
+        formula.add_clause(&clause.iter().map(|x| lit(*x)).collect::<Vec<_>>());
+    }
+    formula
+}
+#[test]
+fn test_model() {
+    let solver = Solver::default()
+        .add_formula(&formula(&[&[1], &[-1, 2], &[-2, -3], &[3, 4, -1]]))
+        .solve();
+    assert_eq!(solver.state(), SolveState::Sat);
+    let model = solver.model().unwrap();
+    assert_eq!(model, vec![lit(1), lit(2), lit(-3), lit(4)]);
+    assert!(solver.model_iter().eq(model.iter().copied()));
+    assert_eq!(solver.value(lit(1)), Some(true));
+    assert_eq!(solver.value(lit(-1)), Some(false));
+    assert_eq!(solver.value(lit(-3)), Some(true));
+    assert_eq!(solver.value(lit(5)), None);
+}
+#[test]
+fn test_model_unsat() {
+    let solver = Solver::default()
+        .add_formula(&formula(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]))
+        .solve();
+    assert_eq!(solver.state(), SolveState::Unsat);
+    assert_eq!(solver.model(), None);
+    assert_eq!(solver.model_iter().count(), 0);
+    assert_eq!(solver.value(lit(1)), None);
+}
+#[test]
+fn test_solve_with_assumptions() {
+    let mut solver = Solver::default().add_formula(&formula(&[&[1, 2], &[-1, 3], &[-2, 3]]));
+    assert_eq!(solver.solve_with_assumptions(&[lit(-3)]), SolveState::Unsat);
+    assert_eq!(solver.solve_with_assumptions(&[lit(-1)]), SolveState::Sat);
+    assert_eq!(solver.value(lit(2)), Some(true));
+    assert_eq!(solver.value(lit(3)), Some(true));
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Sat);
+
+    solver.add_clause(&[lit(-3), lit(4)]);
+    assert_eq!(solver.solve_with_assumptions(&[lit(-4)]), SolveState::Unsat);
+    solver.add_clause(&[lit(-4)]);
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unsat);
+    assert_eq!(solver.solve_with_assumptions(&[lit(1)]), SolveState::Unsat);
+}
+#[test]
+fn test_solve_with_assumptions_new_vars() {
+    let mut solver = Solver::default().add_formula(&formula(&[&[1, 2]]));
+    assert_eq!(solver.solve_with_assumptions(&[lit(-5)]), SolveState::Sat);
+    assert_eq!(solver.value(lit(5)), Some(false));
+    solver.add_clause(&[lit(-1), lit(6)]);
+    solver.add_clause(&[lit(-2), lit(6)]);
+    assert_eq!(solver.solve_with_assumptions(&[lit(-6)]), SolveState::Unsat);
+    assert_eq!(solver.solve_with_assumptions(&[lit(6)]), SolveState::Sat);
+}
+
+/// Tiny deterministic generator so the randomized tests are reproducible.
+#[test]
+fn test_add_long_clause() {
+    // far too slow if duplicates were found by scanning the clause
+    let len = 100_000;
+    let clause: Vec<Lit> = (0..2 * len)
+        .map(|index| Var::from_index(index % len).lit(false))
+        .collect();
+    let mut solver = Solver::default();
+    solver.add_clause(&clause);
+    solver.add_clause(&[clause[0], !clause[1], clause[1]]);
+    for lit in &clause[1..len] {
+        solver.add_clause(&[!*lit]);
+    }
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Sat);
+    assert_eq!(solver.value(clause[0]), Some(true));
+    solver.add_clause(&[!clause[0]]);
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unsat);
+}
+#[test]
+fn test_incremental_random() {
+    let var_count = 10;
+    let mut rng = Lcg(7);
+    for _ in 0..30 {
+        let mut solver = Solver::default();
+        let mut clauses = Vec::new();
+        for _ in 0..6 {
+            for _ in 0..8 {
+                let clause: Vec<Lit> = (0..3).map(|_| rng.lit(var_count)).collect();
+                solver.add_clause(&clause);
+                clauses.push(clause);
+            }
+            let assumptions: Vec<Lit> = (0..rng.next(4)).map(|_| rng.lit(var_count)).collect();
+            let expected = brute_force(&clauses, &assumptions, var_count);
+            let state = solver.solve_with_assumptions(&assumptions);
+            assert_eq!(state == SolveState::Sat, expected);
+            let failed = solver.failed_assumptions();
+            assert!(failed.iter().all(|lit| assumptions.contains(lit)));
+            if !expected && !failed.is_empty() {
+                assert!(!brute_force(&clauses, failed, var_count));
+            }
+            if expected {
+                let model = solver.model().unwrap();
+                assert!(assumptions.iter().all(|lit| model.contains(lit)));
+                assert!(clauses
+                    .iter()
+                    .all(|clause| clause.iter().any(|lit| model.contains(lit))));
+            }
+        }
+    }
+}
+#[test]
+fn test_failed_assumptions() {
+    let mut solver = Solver::default().add_formula(&formula(&[&[-1, 2], &[-2, 3], &[-4, -3]]));
+    assert_eq!(
+        solver.solve_with_assumptions(&[lit(5), lit(1), lit(-6), lit(4)]),
+        SolveState::Unsat
+    );
+    let mut failed = solver.failed_assumptions().to_vec();
+    failed.sort();
+    assert_eq!(failed, vec![lit(1), lit(4)]);
+
+    assert_eq!(
+        solver.solve_with_assumptions(&[lit(2), lit(-2)]),
+        SolveState::Unsat
+    );
+    let mut failed = solver.failed_assumptions().to_vec();
+    failed.sort();
+    assert_eq!(failed, vec![lit(2), lit(-2)]);
+
+    assert_eq!(solver.solve_with_assumptions(&[lit(3)]), SolveState::Sat);
+    assert!(solver.failed_assumptions().is_empty());
+    solver.add_clause(&[lit(-3)]);
+    assert_eq!(solver.solve_with_assumptions(&[lit(3)]), SolveState::Unsat);
+    assert_eq!(solver.failed_assumptions(), &[lit(3)]);
+    solver.add_clause(&[lit(3)]);
+    assert_eq!(solver.solve_with_assumptions(&[lit(1)]), SolveState::Unsat);
+    assert!(solver.failed_assumptions().is_empty());
+}
+#[test]
+fn test_polarity_modes() {
+    let modes = [
+        PolarityMode::True,
+        PolarityMode::False,
+        PolarityMode::Random,
+        PolarityMode::Saved,
+        PolarityMode::Target,
+    ];
+    let configs = modes.map(|polarity| SolverConfig {
+        polarity,
+        ..Default::default()
+    });
+    check_against_brute_force(&mut Lcg(11), 10, 42, configs);
+}
+#[test]
+fn test_phase_saving() {
+    let saved_value = |polarity| {
+        let mut solver = Solver::with_config(SolverConfig {
+            polarity,
+            ..Default::default()
+        });
+        solver.add_clause(&[lit(1), lit(2)]);
+        assert_eq!(solver.solve_with_assumptions(&[lit(3)]), SolveState::Sat);
+        assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Sat);
+        solver.value(lit(3))
+    };
+    assert_eq!(saved_value(PolarityMode::Saved), Some(true));
+    assert_eq!(saved_value(PolarityMode::False), Some(false));
+    assert_eq!(saved_value(PolarityMode::True), Some(true));
+    let solver = Solver::default().add_formula(&formula(&[&[1, 2]])).solve();
+    assert_eq!(solver.model(), Some(vec![lit(-1), lit(2)]));
+}
+#[test]
+fn test_restart_policies() {
+    let policies = [
+        RestartPolicy::Never,
+        RestartPolicy::Fixed { interval: 1 },
+        RestartPolicy::Geometric {
+            initial: 2,
+            factor: 1.5,
+        },
+        RestartPolicy::Luby { unit: 1 },
+        RestartPolicy::default(),
+    ];
+    let configs = policies.map(|restart| SolverConfig {
+        restart,
+        ..Default::default()
+    });
+    check_against_brute_force(&mut Lcg(13), 12, 52, configs);
+}
+#[test]
+fn test_reduce_learnt_clauses() {
+    let var_count = 40;
+    let mut rng = Lcg(17);
+    for _ in 0..10 {
+        let clauses: Vec<Vec<Lit>> = (0..170)
+            .map(|_| (0..3).map(|_| rng.lit(var_count)).collect())
+            .collect();
+        let states: Vec<SolveState> = [0, 1, 5]
+            .into_iter()
+            .map(|reduce_interval| {
+                let mut solver = Solver::with_config(SolverConfig {
+                    reduce_interval,
+                    ..Default::default()
+                });
+                for clause in &clauses {
+                    solver.add_clause(clause);
+                }
+                let state = solver.solve_with_assumptions(&[]);
+                if let Some(model) = solver.model() {
+                    assert!(clauses
+                        .iter()
+                        .all(|clause| clause.iter().any(|lit| model.contains(lit))));
+                }
+                state
+            })
+            .collect();
+        assert!(states.iter().all(|state| *state == states[0]));
+    }
+}
+#[test]
+fn test_minimize_modes() {
+    let modes = [
+        MinimizeMode::None,
+        MinimizeMode::Basic,
+        MinimizeMode::Recursive,
+    ];
+    let configs = modes.map(|minimize| SolverConfig {
+        minimize,
+        ..Default::default()
+    });
+    check_against_brute_force(&mut Lcg(19), 12, 52, configs);
+}
+#[test]
+fn test_verify_model() {
+    let var_count = 12;
+    let mut rng = Lcg(23);
+    for _ in 0..20 {
+        let mut formula = CnfFormula::new();
+        for _ in 0..45 {
+            let clause: Vec<Lit> = (0..3).map(|_| rng.lit(var_count)).collect();
+            formula.add_clause(&clause);
+        }
+        let mut solver = Solver::with_config(SolverConfig {
+            verify_model: true,
+            ..Default::default()
+        })
+        .add_formula(&formula);
+        let assumptions = [rng.lit(var_count), rng.lit(var_count)];
+        if solver.solve_with_assumptions(&assumptions) == SolveState::Sat {
+            let model = solver.model().unwrap();
+            assert_eq!(formula.evaluate(&model), Ok(()));
+            assert_eq!(solver.verify_error(), None);
+            assert!(solver.check_satisfied());
+        } else {
+            assert!(!solver.check_satisfied());
+        }
+    }
+}
+#[test]
+fn test_limits() {
+    let with_limits = |limits| {
+        Solver::with_config(SolverConfig {
+            limits,
+            ..Default::default()
+        })
+        .add_formula(&pigeon_hole(6))
+    };
+    let mut solver = with_limits(Limits {
+        conflicts: Some(50),
+        ..Default::default()
+    });
+    let mut calls = 1;
+    while solver.solve_with_assumptions(&[]) == SolveState::Unknown {
+        assert_eq!(solver.state(), SolveState::Unknown);
+        assert_eq!(solver.model(), None);
+        assert!(solver.failed_assumptions().is_empty());
+        calls += 1;
+    }
+    assert!(calls > 1);
+    assert_eq!(solver.state(), SolveState::Unsat);
+
+    for limits in [
+        Limits {
+            decisions: Some(10),
+            ..Default::default()
+        },
+        Limits {
+            propagations: Some(100),
+            ..Default::default()
+        },
+        Limits {
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        },
+    ] {
+        let solver = with_limits(limits).solve();
+        assert_eq!(solver.state(), SolveState::Unknown, "{:?}", limits);
+    }
+    let solver = with_limits(Limits {
+        timeout: Some(Duration::from_secs(3600)),
+        ..Default::default()
+    })
+    .solve();
+    assert_eq!(solver.state(), SolveState::Unsat);
+}
+#[test]
+fn test_interrupt() {
+    let mut solver = Solver::default().add_formula(&pigeon_hole(6));
+    let handle = solver.interrupt_handle();
+    handle.interrupt();
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unknown);
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unsat);
+
+    // interrupt once the search has learnt enough clauses to flush its
+    // proof, far too early to refute 13 pigeons in 12 holes
+    let mut solver = Solver::default().add_formula(&pigeon_hole(12));
+    let handle = solver.interrupt_handle();
+    let (started, search_started) = mpsc::channel();
+    solver.write_proof(StartSignal(Some(started)), ProofFormat::Drat);
+    let interrupter = thread::spawn(move || {
+        search_started.recv().unwrap();
+        handle.interrupt();
+    });
+    let var = |pigeon: usize, hole: usize| Var::from_index(pigeon * 12 + hole).lit(true);
+    assert_eq!(
+        solver.solve_with_assumptions(&[var(0, 0)]),
+        SolveState::Unknown
+    );
+    interrupter.join().unwrap();
+    assert_eq!(solver.model(), None);
+    assert!(solver.failed_assumptions().is_empty());
+    // the assumption is gone with the interrupted search
+    solver.add_clause(&[!var(0, 0)]);
+    solver.add_clause(&[var(0, 1)]);
+    solver.add_clause(&[var(1, 1)]);
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unsat);
+}
+/// A proof sink telling the test that the search is running.
+struct StartSignal(Option<mpsc::Sender<()>>);
+impl Write for StartSignal {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(started) = self.0.take() {
+            started.send(()).unwrap();
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+#[test]
+fn test_stats() {
+    let mut solver = Solver::with_config(SolverConfig {
+        reduce_interval: 1,
+        limits: Limits {
+            conflicts: Some(100),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .add_formula(&pigeon_hole(6));
+    assert_eq!(*solver.stats(), Default::default());
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unknown);
+    let first = solver.stats().clone();
+    assert_eq!(first.conflicts, 100);
+    assert!(first.decisions > 0 && first.propagations > first.decisions);
+    assert!(first.learnt_clauses > 0 && first.learnt_clauses <= first.conflicts);
+    assert!(first.learnt_literals <= first.learnt_literals_unminimized);
+    assert!(first.reductions > 0 && first.deleted_clauses > 0);
+    // phase times are sampled, so they are only estimates of the total
+    assert!(first.propagations > 0);
+
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Unknown);
+    let second = solver.stats();
+    assert_eq!(second.conflicts, 200);
+    assert!(second.decisions > first.decisions);
+    assert!(second.solve_time > first.solve_time);
+    let display = second.to_string();
+    assert!(display.lines().count() > 1);
+    assert!(display.lines().all(|line| line.starts_with("c ")));
+}
+#[test]
+fn test_errors() {
+    for restart in [
+        RestartPolicy::Fixed { interval: 0 },
+        RestartPolicy::Geometric {
+            initial: 10,
+            factor: 0.5,
+        },
+        RestartPolicy::Luby { unit: 0 },
+        RestartPolicy::Glucose {
+            margin: f64::NAN,
+            min_conflicts: 2,
+        },
+    ] {
+        let config = SolverConfig {
+            restart,
+            ..Default::default()
+        };
+        let result = Solver::try_with_config(config);
+        assert!(
+            matches!(result, Err(RsatError::InvalidConfig(_))),
+            "{:?}",
+            restart
+        );
+    }
+
+    let mut solver = Solver::try_with_config(SolverConfig {
+        limits: Limits {
+            conflicts: Some(10),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap()
+    .add_formula(&pigeon_hole(6));
+    assert!(matches!(
+        solver.try_solve_with_assumptions(&[]),
+        Err(RsatError::BudgetExhausted)
+    ));
+    let mut solver = Solver::default().add_formula(&formula(&[&[1, 2], &[-1]]));
+    assert!(solver.try_solve_with_assumptions(&[]).unwrap());
+    assert!(!solver.try_solve_with_assumptions(&[lit(-2)]).unwrap());
+
+    assert_eq!(Lit::try_from_dimacs(3, false).unwrap(), lit(-3));
+    for var in [0, Lit::MAX_VAR + 1] {
+        let error = Lit::try_from_dimacs(var, true).unwrap_err();
+        assert!(matches!(error, RsatError::VarOutOfRange { var: v, .. } if v == var));
+    }
+
+    let parse = |input: &str| -> Result<CnfFormula, RsatError> {
+        Ok(CnfFormula::from_dimacs_reader(input.as_bytes())?)
+    };
+    assert!(parse("p cnf 1 1\n1 0\n").is_ok());
+    let error = parse("p cnf 1 1\n2 0\n").unwrap_err();
+    assert!(matches!(error, RsatError::Dimacs(_)));
+    assert!(error.to_string().contains("out of range"));
+    let source = error.source().unwrap();
+    assert_eq!(source.to_string(), error.to_string());
+}
+#[test]
+fn test_new_var() {
+    let mut solver = Solver::default().add_formula(&formula(&[&[1, -2], &[2, 3]]));
+    assert_eq!(solver.var_count(), 3);
+    let x = solver.new_lit();
+    assert_eq!(x, lit(4));
+    assert_eq!(solver.new_var().to_dimacs(), 5);
+    assert_eq!(solver.var_count(), 5);
+    // unconstrained variables still get a value
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Sat);
+    assert_eq!(solver.model().unwrap().len(), 5);
+    assert!(solver.value(x).is_some());
+    assert_eq!(solver.var_value(x.var()), solver.value(x));
+    assert_eq!(solver.var_value(Var::from_dimacs(6)), None);
+
+    // x <-> (1 and 3), with assumptions on the fresh variable
+    solver.add_clause(&[!x, lit(1)]);
+    solver.add_clause(&[!x, lit(3)]);
+    solver.add_clause(&[x, lit(-1), lit(-3)]);
+    assert_eq!(solver.solve_with_assumptions(&[x]), SolveState::Sat);
+    assert_eq!(solver.value(lit(1)), Some(true));
+    assert_eq!(solver.value(lit(3)), Some(true));
+    solver.add_clause(&[lit(-1)]);
+    assert_eq!(solver.solve_with_assumptions(&[x]), SolveState::Unsat);
+    assert_eq!(solver.failed_assumptions(), &[x]);
+    let y = solver.new_lit();
+    assert_eq!(solver.solve_with_assumptions(&[!x, y]), SolveState::Sat);
+    assert_eq!(solver.value(y), Some(true));
+}
+#[test]
+fn test_declared_vars() {
+    // only the variables in use are allocated, the others are false
+    let input = format!("p cnf {} 1\n2 0\n", Lit::MAX_VAR);
+    let formula = CnfFormula::from_dimacs_reader(input.as_bytes()).unwrap();
+    let mut solver = Solver::default().add_formula(&formula);
+    assert_eq!(solver.var_count(), Lit::MAX_VAR);
+    assert_eq!(solver.solve_with_assumptions(&[]), SolveState::Sat);
+    assert_eq!(solver.value(lit(2)), Some(true));
+    assert_eq!(
+        solver.var_value(Var::from_dimacs(Lit::MAX_VAR)),
+        Some(false)
+    );
+    assert_eq!(solver.model_iter().nth(2), Some(lit(-3)));
+
+    let formula = CnfFormula::from_dimacs_reader("p cnf 5 1\n1 0\n".as_bytes()).unwrap();
+    let solver = Solver::default().add_formula(&formula).solve();
+    assert_eq!(solver.model().unwrap().len(), 5);
+    assert_eq!(solver.get_prop_reason(&lit(1)), None);
+    assert_eq!(solver.get_prop_reason(&lit(3)), None);
+}